@@ -1,4 +1,5 @@
 use std::env;
+use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -35,14 +36,35 @@ const C_FILES: &[&str] = &[
     "libsm64/src/gfx_adapter.c",
     "libsm64/src/libsm64.c",
     "libsm64/src/load_anim_data.c",
+    "libsm64/src/load_audio_data.c",
     "libsm64/src/load_surfaces.c",
     "libsm64/src/load_tex_data.c",
     "libsm64/src/obj_pool.c",
+    "wrapper/mario.c",
+    "wrapper/raycast.c",
 ];
 
+// The audio subsystem is a port of Super Mario 64's sequence player and mixer, every source file in these directories is built
+const AUDIO_C_DIRS: &[&str] = &["libsm64/src/decomp/audio", "libsm64/src/decomp/pc"];
+
 const MARIO_GEO: &str = "libsm64/src/decomp/mario/geo.inc.c";
 
+const LIBSM64_HEADER: &str = "libsm64/src/libsm64.h";
+
+// The bindings target the libsm64 API where the debug print function is registered on its own rather than passed to
+// sm64_global_init, Mario is created at a float position, and SM64MarioState includes particleFlags
+const REQUIRED_API: &str = "sm64_register_debug_print_function";
+
 fn main() {
+    let header = fs::read_to_string(LIBSM64_HEADER)
+        .expect("libsm64 submodule is missing, run `git submodule update --init`");
+    if !header.contains(REQUIRED_API) {
+        panic!(
+            "libsm64 submodule is too old, it must provide {}",
+            REQUIRED_API
+        );
+    }
+
     if !PathBuf::from(MARIO_GEO).exists() {
         Command::new("python3")
             .arg("import-mario-geo.py")
@@ -51,13 +73,38 @@ fn main() {
             .expect("Unable to download mario geometry");
     }
 
-    cc::Build::new()
+    let audio_files = AUDIO_C_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("c"));
+
+    let mut build = cc::Build::new();
+
+    // Keep the results of floating point math identical across targets, otherwise the compiler is
+    // free to fuse multiplies and adds on targets that support it, such as aarch64
+    build
         .files(C_FILES)
+        .files(audio_files)
         .warnings(false)
-        .compile("sm64");
+        .flag_if_supported("-ffp-contract=off")
+        .flag_if_supported("/fp:precise");
+
+    // 32-bit x86 defaults to the x87 FPU which rounds intermediate values differently
+    if env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("x86") {
+        build
+            .flag_if_supported("-msse2")
+            .flag_if_supported("-mfpmath=sse");
+    }
+
+    build.compile("sm64");
 
     let bindings = bindgen::Builder::default()
-        .header("libsm64/src/libsm64.h")
+        .header(LIBSM64_HEADER)
+        .header("wrapper/mario.h")
+        .header("wrapper/raycast.h")
         .generate()
         .expect("Unable to generate libsm64 bindings");
 
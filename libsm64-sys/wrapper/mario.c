@@ -0,0 +1,13 @@
+#include "mario.h"
+
+#include "../libsm64/src/decomp/global_state.h"
+
+SM64_LIB_FN void sm64_mario_reset_health( int32_t marioId, uint16_t health )
+{
+    // sm64_mario_set_health binds the global state of marioId, so gMarioState is that Mario afterwards.
+    // The caller must pass the id of a live Mario, otherwise nothing is bound and another Mario would be changed.
+    sm64_mario_set_health( marioId, health );
+
+    gMarioState->hurtCounter = 0;
+    gMarioState->healCounter = 0;
+}
@@ -0,0 +1,10 @@
+#ifndef LIBSM64_RS_MARIO_H
+#define LIBSM64_RS_MARIO_H
+
+#include "../libsm64/src/libsm64.h"
+
+// Set a Mario's health like sm64_mario_set_health, and also clear the hurt and heal counters so damage or healing
+// that was still pending doesn't keep changing his health afterwards.
+extern SM64_LIB_FN void sm64_mario_reset_health( int32_t marioId, uint16_t health );
+
+#endif
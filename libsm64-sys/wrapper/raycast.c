@@ -0,0 +1,67 @@
+#include "raycast.h"
+
+#include "../libsm64/src/load_surfaces.h"
+
+#define RAYCAST_EPSILON 0.00001f
+
+// Möller-Trumbore ray triangle intersection, returns the distance along the ray or a negative value on a miss
+static float ray_surface_intersect( const float *orig, const float *dir, const struct Surface *surf )
+{
+    float v0[3] = { surf->vertex1[0], surf->vertex1[1], surf->vertex1[2] };
+    float e1[3] = { surf->vertex2[0] - v0[0], surf->vertex2[1] - v0[1], surf->vertex2[2] - v0[2] };
+    float e2[3] = { surf->vertex3[0] - v0[0], surf->vertex3[1] - v0[1], surf->vertex3[2] - v0[2] };
+
+    float p[3] = {
+        dir[1] * e2[2] - dir[2] * e2[1],
+        dir[2] * e2[0] - dir[0] * e2[2],
+        dir[0] * e2[1] - dir[1] * e2[0],
+    };
+
+    float det = e1[0] * p[0] + e1[1] * p[1] + e1[2] * p[2];
+    if( det > -RAYCAST_EPSILON && det < RAYCAST_EPSILON )
+        return -1.0f;
+
+    float invDet = 1.0f / det;
+    float t[3] = { orig[0] - v0[0], orig[1] - v0[1], orig[2] - v0[2] };
+
+    float u = ( t[0] * p[0] + t[1] * p[1] + t[2] * p[2] ) * invDet;
+    if( u < 0.0f || u > 1.0f )
+        return -1.0f;
+
+    float q[3] = {
+        t[1] * e1[2] - t[2] * e1[1],
+        t[2] * e1[0] - t[0] * e1[2],
+        t[0] * e1[1] - t[1] * e1[0],
+    };
+
+    float v = ( dir[0] * q[0] + dir[1] * q[1] + dir[2] * q[2] ) * invDet;
+    if( v < 0.0f || u + v > 1.0f )
+        return -1.0f;
+
+    return ( e2[0] * q[0] + e2[1] * q[1] + e2[2] * q[2] ) * invDet;
+}
+
+SM64_LIB_FN float sm64_static_surface_raycast( float originX, float originY, float originZ, float dirX, float dirY, float dirZ, float maxDist, struct SM64SurfaceCollisionData **psurface )
+{
+    float orig[3] = { originX, originY, originZ };
+    float dir[3] = { dirX, dirY, dirZ };
+    float closest = maxDist;
+
+    *psurface = NULL;
+
+    // Group 0 holds the static surfaces, the remaining groups belong to surface objects
+    uint32_t count = loaded_surface_iter_group_size( 0 );
+    for( uint32_t i = 0; i < count; i++ )
+    {
+        struct Surface *surf = loaded_surface_iter_get_at_index( 0, i );
+        float dist = ray_surface_intersect( orig, dir, surf );
+
+        if( dist >= 0.0f && dist <= closest )
+        {
+            closest = dist;
+            *psurface = (struct SM64SurfaceCollisionData *)surf;
+        }
+    }
+
+    return closest;
+}
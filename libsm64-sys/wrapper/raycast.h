@@ -0,0 +1,11 @@
+#ifndef LIBSM64_RS_RAYCAST_H
+#define LIBSM64_RS_RAYCAST_H
+
+#include "../libsm64/src/libsm64.h"
+
+// Cast a ray against the static surfaces loaded with sm64_static_surfaces_load. The direction
+// must be normalized. Returns the distance along the ray to the closest surface within maxDist
+// and stores that surface in psurface, or stores NULL if nothing was hit.
+extern SM64_LIB_FN float sm64_static_surface_raycast( float originX, float originY, float originZ, float dirX, float dirY, float dirZ, float maxDist, struct SM64SurfaceCollisionData **psurface );
+
+#endif
@@ -57,15 +57,122 @@ for triangle in mario.geometry().triangles() {
 ```
 */
 
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::io::{BufReader, Read};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use once_cell::sync::OnceCell;
 use sha::sha1;
 use sha::utils::{Digest, DigestExt};
 
+/// The rate `Mario::tick` should be called at, in ticks per second
+pub const TICK_RATE_HZ: u32 = 30;
+/// The duration of a single tick of Mario's logic
+pub const TICK_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TICK_RATE_HZ as u64);
+/// The sample rate of the audio produced by libsm64, in samples per second
+pub const AUDIO_SAMPLE_RATE_HZ: u32 = 32000;
+/// The number of `i16` samples a single call to `Sm64::audio_tick` can write, two blocks of 544 interleaved stereo frames
+pub const AUDIO_BUFFER_LEN: usize = AUDIO_FRAMES_HIGH as usize * 4;
+/// The number of stereo frames `Sm64::audio_tick` aims to keep queued, while fewer are queued each call produces slightly more audio than is played in a tick
+pub const AUDIO_TARGET_QUEUED_FRAMES: u32 = 1100;
+
+/// The largest reverb level accepted by `Sm64::set_reverb`
+pub const MAX_REVERB: u8 = 127;
+
+/// The smallest distance the collision geometry can represent in sm64 units, verticies are stored as `i16` so every coordinate is rounded to a whole unit
+pub const COLLISION_PRECISION: f32 = 1.0;
+
+const ACT_GROUP_MASK: u32 = 0x0000_01C0;
+
+// The number of stereo frames in each of the two blocks sm64_audio_tick produces while below the target queue size
+const AUDIO_FRAMES_HIGH: u32 = 544;
+
+// Eight wedges of health, as Mario spawns with
+const FULL_HEALTH: u16 = 0x880;
+
+// Returned by sm64_get_current_background_music when no music is playing
+const NO_MUSIC: u16 = 0xFFFF;
+
 const VALID_HASH: &str = "9bef1128717f958171a4afac3ed78ee2bb4e86ce";
 
+const SUPPORTED_ROMS: &[(RomRegion, &str)] = &[(RomRegion::Us, VALID_HASH)];
+
+const KNOWN_ROMS: &[(RomRegion, &str)] = &[
+    (RomRegion::Us, VALID_HASH),
+    (RomRegion::Jp, "8a20a5c83d6ceb0f0506cfc9fa20d8f438cafe51"),
+    (RomRegion::Eu, "4ac5721683d0e0b6bbb561b58a71740845dceea9"),
+];
+
+/// The regional versions of Super Mario 64
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RomRegion {
+    /// Super Mario 64 (USA)
+    Us,
+    /// Super Mario 64 (Japan), recognized but not supported by libsm64
+    Jp,
+    /// Super Mario 64 (Europe), recognized but not supported by libsm64
+    Eu,
+}
+
+impl RomRegion {
+    // Identify a rom by its SHA1 hash
+    fn from_hash(rom_hash: &str) -> Option<Self> {
+        KNOWN_ROMS
+            .iter()
+            .find(|(_, hash)| *hash == rom_hash)
+            .map(|(region, _)| *region)
+    }
+}
+
+/// The roms that can be used with `Sm64::new`, as pairs of their region and SHA1 hash
+pub fn supported_roms() -> &'static [(RomRegion, &'static str)] {
+    SUPPORTED_ROMS
+}
+
+/// The height libsm64 reports when there is no floor beneath a point
+const FLOOR_LOWER_LIMIT: f32 = -11000.0;
+/// How far above a floor Mario can be while still considered to be touching it
+const FLOOR_CONTACT_DISTANCE: f32 = 4.0;
+/// The height above Mario's feet and the radius used to find the walls he is touching, slightly larger than the 50 unit radius the engine pushes him out to
+const WALL_CONTACT_OFFSET_Y: f32 = 60.0;
+const WALL_CONTACT_RADIUS: f32 = 52.0;
+
 static SM64: once_cell::sync::OnceCell<Sm64Inner> = OnceCell::new();
+static TERMINATED: AtomicBool = AtomicBool::new(false);
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+static AUDIO_INITIALIZED: AtomicBool = AtomicBool::new(false);
+static INSTANCE: AtomicBool = AtomicBool::new(false);
+static WORLD_BOUNDS: Mutex<Option<(Point3<f32>, Point3<f32>)>> = Mutex::new(None);
+static HANDEDNESS: Mutex<Handedness> = Mutex::new(Handedness::Right);
+static DEBUG_HANDLER: Mutex<Option<DebugHandler>> = Mutex::new(None);
+
+type DebugHandler = Box<dyn FnMut(&str) + Send>;
+
+// Registered before every sm64_global_init so a handler can be installed or replaced at any time
+unsafe extern "C" fn debug_print(message: *const std::os::raw::c_char) {
+    if message.is_null() {
+        return;
+    }
+
+    let message = std::ffi::CStr::from_ptr(message).to_string_lossy();
+
+    // Nothing may panic across the FFI boundary, and a message printed while the handler is running is dropped rather than deadlocking
+    let mut handler = match DEBUG_HANDLER.try_lock() {
+        Ok(handler) => handler,
+        Err(std::sync::TryLockError::Poisoned(err)) => err.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => return,
+    };
+
+    if let Some(handler) = handler.as_mut() {
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| handler(&message)));
+    }
+}
 
 /// An error that can occur
 #[derive(Debug)]
@@ -76,6 +183,24 @@ pub enum Error {
     InvalidMarioPosition,
     /// The rom proivided must be Super Mario 64 (USA), with a SHA1 hash of '9bef1128717f958171a4afac3ed78ee2bb4e86ce'
     InvalidRom(String),
+    /// The rom is a known version of Super Mario 64, but libsm64 can only extract Mario's data from the roms in `supported_roms`
+    UnsupportedRegion(RomRegion),
+    /// The verticies of a triangle have no area once quantized to sm64 units
+    DegenerateTriangle,
+    /// libsm64 was torn down with `Sm64::terminate` and must be reinitialized with `Sm64::new`
+    NotInitialized,
+    /// Another `Sm64` already exists, libsm64's state is global so only one can exist at a time
+    AlreadyInitialized,
+    /// A `LevelScale` can not represent the level with the required precision
+    InvalidScale(String),
+    /// A coordinate in world units is outside the range of an `i16` once converted to sm64 units
+    CoordinateOutOfRange(f32),
+    /// `Sm64Builder::build` was called without a rom, or after the rom was already used by an earlier `build`
+    MissingRom,
+    /// The value does not match any `Surface` type
+    UnknownSurface(u16),
+    /// The value does not match any `Terrain` type
+    UnknownTerrain(u16),
 }
 
 impl std::fmt::Display for Error {
@@ -91,6 +216,32 @@ impl std::fmt::Display for Error {
                 "Invalid Super Mario 64 rom: found hash '{}', expected hash '{}'",
                 hash, VALID_HASH
             ),
+            Error::UnsupportedRegion(region) => write!(
+                f,
+                "Unsupported Super Mario 64 rom region {:?}, expected hash '{}'",
+                region, VALID_HASH
+            ),
+            Error::DegenerateTriangle => write!(
+                f,
+                "Degenerate triangle, the vertices have no area once quantized to sm64 units"
+            ),
+            Error::NotInitialized => write!(
+                f,
+                "libsm64 is not initialized, it must be reinitialized after calling terminate"
+            ),
+            Error::AlreadyInitialized => write!(
+                f,
+                "libsm64 is already initialized, the existing Sm64 must be dropped first"
+            ),
+            Error::InvalidScale(reason) => write!(f, "Invalid level scale: {}", reason),
+            Error::CoordinateOutOfRange(v) => write!(
+                f,
+                "Coordinate {} is outside the range of sm64 units once scaled",
+                v
+            ),
+            Error::MissingRom => write!(f, "No Super Mario 64 rom was provided to the builder"),
+            Error::UnknownSurface(value) => write!(f, "Unknown surface type {:#06x}", value),
+            Error::UnknownTerrain(value) => write!(f, "Unknown terrain type {:#06x}", value),
         }
     }
 }
@@ -103,27 +254,161 @@ impl From<std::io::Error> for Error {
 
 struct Sm64Inner {
     texture_data: Vec<u8>,
-    #[allow(dead_code)]
-    rom_data: Vec<u8>,
+    rom_data: RomData,
+    region: RomRegion,
+}
+
+// libsm64 reads Mario's animations directly out of the rom, so it must be kept alive for as long as libsm64 is initialized
+enum RomData {
+    Owned(Vec<u8>),
+    Shared(Arc<[u8]>),
+}
+
+impl Sm64Inner {
+    fn reinit(&self) {
+        // The texture is identical on every init, so it is decoded into a scratch buffer
+        // rather than overwriting the shared one
+        let mut texture_data = vec![0; self.texture_data.len()];
+
+        unsafe {
+            libsm64_sys::sm64_register_debug_print_function(Some(debug_print));
+            libsm64_sys::sm64_global_init(
+                self.rom_data.as_slice().as_ptr(),
+                texture_data.as_mut_ptr(),
+            );
+        }
+    }
+}
+
+impl RomData {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            RomData::Owned(data) => data.as_slice(),
+            RomData::Shared(data) => data,
+        }
+    }
+}
+
+const V64_MAGIC: [u8; 4] = [0x37, 0x80, 0x40, 0x12];
+const N64_MAGIC: [u8; 4] = [0x40, 0x12, 0x37, 0x80];
+
+// Convert a byte-swapped (.v64) or little-endian (.n64) rom to the big-endian (.z64) order libsm64 expects,
+// roms without a recognized header are left unchanged
+fn normalize_rom_byte_order(rom: &mut [u8]) {
+    if rom.starts_with(&V64_MAGIC) {
+        for chunk in rom.chunks_exact_mut(2) {
+            chunk.swap(0, 1);
+        }
+    } else if rom.starts_with(&N64_MAGIC) {
+        for chunk in rom.chunks_exact_mut(4) {
+            chunk.reverse();
+        }
+    }
 }
 
 /// The core interface to libsm64
-pub struct Sm64;
+///
+/// libsm64's state is global and has no internal locking, so `Sm64`, `Mario`, and `DynamicSurface` are `Send` but not `Sync`.
+/// They can be moved to another thread, but should all be used from the same thread as each other
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<libsm64::Sm64>();
+/// ```
+pub struct Sm64 {
+    _not_sync: PhantomData<Cell<()>>,
+}
 
 impl Sm64 {
-    /// Create a new instance of Sm64, requires a Super Mario 64 rom to extra Mario's texture and animation data from
+    /// Create a new instance of Sm64, requires a Super Mario 64 rom to extra Mario's texture and animation data from.
+    /// The rom may be in any of the .z64, .v64, or .n64 byte orders
+    ///
+    /// Only one `Sm64` can exist at a time, `Error::AlreadyInitialized` is returned until the existing one is dropped
     pub fn new<R: Read>(rom: R) -> Result<Self, Error> {
-        let mut rom_file = BufReader::new(rom);
-        let mut rom_data = Vec::new();
-        rom_file.read_to_end(&mut rom_data)?;
+        Sm64Builder::new().rom(rom).build()
+    }
+
+    /// Create a new instance of Sm64 from a rom that is already in memory, passing a `Vec<u8>` takes ownership of it without a copy
+    pub fn from_bytes(rom: impl Into<Vec<u8>>) -> Result<Self, Error> {
+        Self::init(RomData::Owned(rom.into()), None, None)
+    }
+
+    /// Create a new instance of Sm64 from the rom file at `path`, if the file can't be opened the returned `Error::Io` includes the path
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let rom = std::fs::File::open(path).map_err(|err| {
+            std::io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+        })?;
 
-        let rom_hash = sha1::Sha1::default().digest(&*rom_data).to_hex();
+        Self::new(rom)
+    }
+
+    /// Create a new instance of Sm64 that passes libsm64's debug messages to `handler`, any invalid UTF-8 in a message is replaced with `U+FFFD`.
+    /// The handler is removed when the `Sm64` is dropped
+    ///
+    /// A panic in the handler is caught before it reaches libsm64, and any message printed while the handler is running is dropped.
+    ///
+    /// **Note:** The handler is called from within libsm64, calling back into libsm64 from the handler will deadlock
+    pub fn with_debug_handler<R: Read, F: FnMut(&str) + Send + 'static>(
+        rom: R,
+        handler: F,
+    ) -> Result<Self, Error> {
+        Sm64Builder::new().rom(rom).debug_handler(handler).build()
+    }
+
+    /// Create a new instance of Sm64 from a rom that is shared with the rest of your application, the rom will be used without being copied
+    pub fn from_shared(rom: Arc<[u8]>) -> Result<Self, Error> {
+        Self::init(RomData::Shared(rom), None, None)
+    }
+
+    fn init(
+        rom_data: RomData,
+        expected_region: Option<RomRegion>,
+        debug_handler: Option<DebugHandler>,
+    ) -> Result<Self, Error> {
+        // Roms in .v64 or .n64 byte order are converted to .z64 before hashing, a shared rom has to be copied to be converted
+        let rom_data = match rom_data {
+            RomData::Owned(mut data) => {
+                normalize_rom_byte_order(&mut data);
+                RomData::Owned(data)
+            }
+            RomData::Shared(data)
+                if data.starts_with(&V64_MAGIC) || data.starts_with(&N64_MAGIC) =>
+            {
+                let mut data = data.to_vec();
+                normalize_rom_byte_order(&mut data);
+                RomData::Owned(data)
+            }
+            shared => shared,
+        };
+
+        let rom_hash = sha1::Sha1::default().digest(rom_data.as_slice()).to_hex();
+
+        let region = match RomRegion::from_hash(&rom_hash) {
+            Some(region) if expected_region.is_some_and(|expected| expected != region) => {
+                return Err(Error::UnsupportedRegion(region))
+            }
+            Some(region)
+                if SUPPORTED_ROMS
+                    .iter()
+                    .any(|(supported, _)| *supported == region) =>
+            {
+                region
+            }
+            Some(region) => return Err(Error::UnsupportedRegion(region)),
+            None => return Err(Error::InvalidRom(rom_hash)),
+        };
 
-        if rom_hash != VALID_HASH {
-            return Err(Error::InvalidRom(rom_hash));
+        if INSTANCE.swap(true, Ordering::SeqCst) {
+            return Err(Error::AlreadyInitialized);
         }
 
-        let _sm64 = SM64.get_or_init(|| {
+        // Installed only once this is the live instance, so the handler sees the messages from initialization
+        if debug_handler.is_some() {
+            *DEBUG_HANDLER.lock().unwrap_or_else(|e| e.into_inner()) = debug_handler;
+        }
+
+        let sm64 = SM64.get_or_init(|| {
             let mut texture_data = vec![
                 0;
                 (libsm64_sys::SM64_TEXTURE_WIDTH * libsm64_sys::SM64_TEXTURE_HEIGHT)
@@ -132,20 +417,67 @@ impl Sm64 {
             ];
 
             unsafe {
+                libsm64_sys::sm64_register_debug_print_function(Some(debug_print));
                 libsm64_sys::sm64_global_init(
-                    rom_data.as_mut_ptr(),
+                    rom_data.as_slice().as_ptr(),
                     texture_data.as_mut_ptr(),
-                    None,
                 );
             }
 
             Sm64Inner {
                 texture_data,
                 rom_data,
+                region,
             }
         });
 
-        Ok(Self)
+        if TERMINATED.swap(false, Ordering::SeqCst) {
+            sm64.reinit();
+        }
+
+        Ok(Self {
+            _not_sync: PhantomData,
+        })
+    }
+
+    /// The region of the rom libsm64 was initialized with
+    pub fn region(&self) -> RomRegion {
+        SM64.get().expect("Sm64::new() must of been called").region
+    }
+
+    /// Delete every Mario and dynamic surface, unload the static geometry, and reset libsm64's gameplay state.
+    /// The rom is not reloaded and the texture is kept, making this much faster than creating a new `Sm64`
+    ///
+    /// If `audio_init` was called the audio subsystem is initialized again, so audio keeps working after the reset.
+    ///
+    /// **Note:** Any existing `Mario` or `DynamicSurface` will no longer be simulated, and are safe to drop afterwards
+    pub fn reset(&mut self) {
+        let sm64 = SM64.get().expect("Sm64::new() must of been called");
+
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+        if !TERMINATED.swap(false, Ordering::SeqCst) {
+            unsafe { libsm64_sys::sm64_global_terminate() }
+        }
+
+        sm64.reinit();
+        self.clear_world_bounds();
+
+        // Terminating tore down the audio state along with everything else
+        if AUDIO_INITIALIZED.swap(false, Ordering::SeqCst) {
+            self.audio_init();
+        }
+    }
+
+    /// Explicitly tear down libsm64's global state, the texture remains valid and calling `Sm64::new` again will reinitialize libsm64.
+    /// The audio subsystem is torn down too, `audio_init` must be called again after reinitializing
+    ///
+    /// **Note:** Any `Mario` or `DynamicSurface` created before terminating will no longer be simulated, and are safe to drop afterwards
+    pub fn terminate(self) {
+        if !TERMINATED.swap(true, Ordering::SeqCst) {
+            GENERATION.fetch_add(1, Ordering::SeqCst);
+            AUDIO_INITIALIZED.store(false, Ordering::SeqCst);
+            unsafe { libsm64_sys::sm64_global_terminate() }
+        }
     }
 
     /// A texture atlas that can be applied to the Mario geometry
@@ -163,8 +495,18 @@ impl Sm64 {
     }
 
     /// Create a new instancec of Mario that spawns at the point indicated by x/y/z, he must be placed above a surface or an error will be returned
+    ///
+    /// libsm64 grows its pool of Marios as needed, so there is no limit on how many can be created
     pub fn create_mario(&mut self, x: i16, y: i16, z: i16) -> Result<Mario, Error> {
-        let mario_id = unsafe { libsm64_sys::sm64_mario_create(x, y, z) };
+        if TERMINATED.load(Ordering::SeqCst) {
+            return Err(Error::NotInitialized);
+        }
+
+        let x = match *HANDEDNESS.lock().unwrap() {
+            Handedness::Right => x as f32,
+            Handedness::Left => -(x as f32),
+        };
+        let mario_id = unsafe { libsm64_sys::sm64_mario_create(x, y as f32, z as f32) };
 
         if mario_id < 0 {
             Err(Error::InvalidMarioPosition)
@@ -174,11 +516,31 @@ impl Sm64 {
     }
 
     /// Create a dynamic surface that can have its position and rotation updated at runtime, good for moving platforms
+    ///
+    /// **Note:** All surfaces are shared by every Mario, libsm64 has no collision layers or way to hide a surface from a specific Mario
     pub fn create_dynamic_surface(
         &mut self,
         geometry: &[LevelTriangle],
         transform: SurfaceTransform,
     ) -> DynamicSurface {
+        self.create_dynamic_surface_raw(as_raw_triangles(geometry), transform)
+    }
+
+    /// Create a dynamic surface from triangles with raw surface types, see `RawLevelTriangle`
+    pub fn create_dynamic_surface_raw(
+        &mut self,
+        geometry: &[RawLevelTriangle],
+        transform: SurfaceTransform,
+    ) -> DynamicSurface {
+        debug_assert!(
+            !TERMINATED.load(Ordering::SeqCst),
+            "libsm64 must be initialized before creating a dynamic surface"
+        );
+        debug_assert!(
+            geometry.len() <= u32::MAX as usize,
+            "Too many triangles in dynamic surface"
+        );
+
         let id = unsafe {
             let surface_object = libsm64_sys::SM64SurfaceObject {
                 transform: transform.into(),
@@ -188,11 +550,25 @@ impl Sm64 {
             libsm64_sys::sm64_surface_object_create(&surface_object as *const _)
         };
 
-        DynamicSurface::new(id)
+        DynamicSurface::new(id, transform)
     }
 
     /// Load the static level geometry, used for collision detection
     pub fn load_level_geometry(&mut self, geometry: &[LevelTriangle]) {
+        self.load_level_geometry_raw(as_raw_triangles(geometry))
+    }
+
+    /// Load static level geometry made of triangles with raw surface types, see `RawLevelTriangle`
+    pub fn load_level_geometry_raw(&mut self, geometry: &[RawLevelTriangle]) {
+        debug_assert!(
+            !TERMINATED.load(Ordering::SeqCst),
+            "libsm64 must be initialized before loading level geometry"
+        );
+        debug_assert!(
+            geometry.len() <= u32::MAX as usize,
+            "Too many triangles in level geometry"
+        );
+
         unsafe {
             libsm64_sys::sm64_static_surfaces_load(
                 geometry.as_ptr() as *const _,
@@ -200,32 +576,490 @@ impl Sm64 {
             )
         }
     }
+
+    /// Limit every Mario to the box between `min` and `max`, Mario's position and geometry are clamped during each `tick` and his velocity is zeroed along any clamped axis.
+    /// The bounds are cleared by `reset` and when the `Sm64` is dropped
+    pub fn set_world_bounds(&mut self, min: Point3<f32>, max: Point3<f32>) {
+        let bounds = (
+            Point3 {
+                x: min.x.min(max.x),
+                y: min.y.min(max.y),
+                z: min.z.min(max.z),
+            },
+            Point3 {
+                x: min.x.max(max.x),
+                y: min.y.max(max.y),
+                z: min.z.max(max.z),
+            },
+        );
+
+        *WORLD_BOUNDS.lock().unwrap() = Some(bounds);
+    }
+
+    /// Remove the limits set by `set_world_bounds`
+    pub fn clear_world_bounds(&mut self) {
+        *WORLD_BOUNDS.lock().unwrap() = None;
+    }
+
+    /// The minimum and maximum corners of the box Mario is limited to. By default there are no bounds and Mario is limited only by the level geometry
+    pub fn world_bounds(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        *WORLD_BOUNDS.lock().unwrap()
+    }
+
+    /// Check if a point is above a floor and below any ceiling of the loaded geometry, useful for rejecting spawn points or object placements outside of the playable area
+    pub fn point_in_bounds(&self, p: Point3<f32>) -> bool {
+        let floor = match find_floor_height(p) {
+            Some(floor) => floor,
+            None => return false,
+        };
+
+        let mut ceil_surface = std::ptr::null_mut();
+        let ceil = unsafe {
+            libsm64_sys::sm64_surface_find_ceil(p.x, p.y, p.z, &mut ceil_surface as *mut _)
+        };
+
+        p.y >= floor && p.y <= ceil
+    }
+
+    /// Find the floor below a point, returning its height along with the surface type and terrain of the floor triangle.
+    /// When there are several floors below the point the highest one is used, matching sm64 this includes floors up to 78 units above `y`. Returns `None` if there is no floor below the point.
+    /// As with `raycast` the surface type or terrain is `None` if it is not one that sm64 recognizes, such as those loaded with `load_level_geometry_raw`
+    pub fn floor_height(
+        &self,
+        x: f32,
+        y: f32,
+        z: f32,
+    ) -> Option<(f32, Option<Surface>, Option<Terrain>)> {
+        let (height, floor) = find_floor(Point3 { x, y, z })?;
+
+        Some((
+            height,
+            Surface::from_raw(floor.type_ as u16),
+            Terrain::from_raw(floor.terrain),
+        ))
+    }
+
+    /// Cast a ray from `origin` along `dir` against the static level geometry, returning the closest hit within `max_dist`. `dir` does not need to be normalized
+    pub fn raycast(&self, origin: Point3<f32>, dir: Point3<f32>, max_dist: f32) -> Option<RayHit> {
+        let len = (dir.x * dir.x + dir.y * dir.y + dir.z * dir.z).sqrt();
+        if len == 0.0 || !len.is_finite() || max_dist < 0.0 {
+            return None;
+        }
+        let dir = Point3 {
+            x: dir.x / len,
+            y: dir.y / len,
+            z: dir.z / len,
+        };
+
+        let mut surface: *mut libsm64_sys::SM64SurfaceCollisionData = std::ptr::null_mut();
+        let distance = unsafe {
+            libsm64_sys::sm64_static_surface_raycast(
+                origin.x,
+                origin.y,
+                origin.z,
+                dir.x,
+                dir.y,
+                dir.z,
+                max_dist,
+                &mut surface as *mut _,
+            )
+        };
+
+        if surface.is_null() {
+            return None;
+        }
+        let surface = unsafe { *surface };
+
+        Some(RayHit {
+            point: Point3 {
+                x: origin.x + dir.x * distance,
+                y: origin.y + dir.y * distance,
+                z: origin.z + dir.z * distance,
+            },
+            normal: Point3 {
+                x: surface.normal.x,
+                y: surface.normal.y,
+                z: surface.normal.z,
+            },
+            distance,
+            surface: Surface::from_raw(surface.type_ as u16),
+            terrain: Terrain::from_raw(surface.terrain),
+        })
+    }
+
+    /// Set the handedness of your world, every `MarioInput`, `MarioState`, `MarioGeometry`, and position given to `create_mario` or `Mario::set_position` is converted to match.
+    /// Level geometry, dynamic surfaces, and world bounds are not converted and must be provided in sm64's right-handed space, `Handedness::convert` can be used to convert them.
+    /// The handedness goes back to `Handedness::Right` when the `Sm64` is dropped
+    pub fn set_handedness(&mut self, handedness: Handedness) {
+        *HANDEDNESS.lock().unwrap() = handedness;
+    }
+
+    /// The current handedness set with `set_handedness`
+    pub fn handedness(&self) -> Handedness {
+        *HANDEDNESS.lock().unwrap()
+    }
+
+    /// Initialize libsm64's audio subsystem, this only needs to be called once and later calls do nothing.
+    ///
+    /// **Note:** The sound banks and music sequences are loaded from the rom, this relies on the rom being kept alive by `Sm64` just like Mario's animations
+    pub fn audio_init(&mut self) {
+        let sm64 = SM64.get().expect("Sm64::new() must of been called");
+
+        if !AUDIO_INITIALIZED.swap(true, Ordering::SeqCst) {
+            unsafe { libsm64_sys::sm64_audio_init(sm64.rom_data.as_slice().as_ptr()) }
+        }
+    }
+
+    /// Advance the audio subsystem and write the produced interleaved stereo samples at `AUDIO_SAMPLE_RATE_HZ` into `out`, returning the number of `i16` samples written.
+    ///
+    /// `queued_frames` is the number of stereo frames your audio output still has waiting to be played, libsm64 uses it to keep that queue near `AUDIO_TARGET_QUEUED_FRAMES`.
+    /// Each call produces two blocks of frames:
+    ///
+    /// - While fewer than `AUDIO_TARGET_QUEUED_FRAMES` frames are queued each block is 544 frames, filling all `AUDIO_BUFFER_LEN` samples, slightly more than a tick's worth
+    /// - Once the queue is at or above `AUDIO_TARGET_QUEUED_FRAMES` each block shrinks to 528 frames, writing `528 * 4` samples, slightly less than a tick's worth
+    ///
+    /// Always use the returned length rather than assuming a full `AUDIO_BUFFER_LEN`. It should be called once per tick, any samples that don't fit in `out` are discarded
+    /// so it should be at least `AUDIO_BUFFER_LEN` long. Nothing is written if `audio_init` has not been called
+    pub fn audio_tick(&mut self, queued_frames: u32, out: &mut [i16]) -> usize {
+        if !AUDIO_INITIALIZED.load(Ordering::SeqCst) {
+            return 0;
+        }
+
+        let mut buffer = [0; AUDIO_BUFFER_LEN];
+        let frames = unsafe {
+            libsm64_sys::sm64_audio_tick(
+                queued_frames,
+                AUDIO_TARGET_QUEUED_FRAMES,
+                buffer.as_mut_ptr(),
+            )
+        };
+
+        // Each frame is written to both blocks, and each block holds a left and right sample per frame
+        let len = (frames as usize * 4).min(buffer.len()).min(out.len());
+        out[..len].copy_from_slice(&buffer[..len]);
+
+        len
+    }
+
+    /// Play a sound effect that is not positioned in the world, the sound is only heard if `audio_init` has been called
+    pub fn play_sound(&mut self, sound: Sound) {
+        self.play_sound_raw(sound.into());
+    }
+
+    /// Play a sound effect from its raw sm64 sound bits, for sounds that are not in `Sound`
+    pub fn play_sound_raw(&mut self, sound_bits: u32) {
+        if AUDIO_INITIALIZED.load(Ordering::SeqCst) {
+            unsafe { libsm64_sys::sm64_play_sound_global(sound_bits as i32) }
+        }
+    }
+
+    /// Start playing a music sequence as the background music, `SeqId` lists the sequences in the rom.
+    /// The music is only heard if `audio_init` has been called
+    pub fn play_music(&mut self, seq_id: u16) {
+        if AUDIO_INITIALIZED.load(Ordering::SeqCst) {
+            unsafe { libsm64_sys::sm64_play_music(0, seq_id, 0) }
+        }
+    }
+
+    /// Immediately stop the current background music
+    pub fn stop_music(&mut self) {
+        if let Some(seq_id) = self.current_music() {
+            unsafe { libsm64_sys::sm64_stop_background_music(seq_id) }
+        }
+    }
+
+    /// Fade out the current background music over `frames` ticks
+    pub fn fadeout_music(&mut self, frames: u16) {
+        if let Some(seq_id) = self.current_music() {
+            unsafe { libsm64_sys::sm64_fadeout_background_music(seq_id, frames) }
+        }
+    }
+
+    /// Set the master volume of all sound effects and music, from `0.0` (silent) to `1.0` (the game's normal volume).
+    /// Values outside of that range are clamped and NaN is treated as silent
+    pub fn set_volume(&mut self, volume: f32) {
+        let volume = if volume.is_nan() {
+            0.0
+        } else {
+            volume.clamp(0.0, 1.0)
+        };
+
+        if AUDIO_INITIALIZED.load(Ordering::SeqCst) {
+            unsafe { libsm64_sys::sm64_set_sound_volume(volume) }
+        }
+    }
+
+    /// Set the amount of reverb applied to all sound effects and music, from `0` (none) to `MAX_REVERB` (127) which matches the range used by sm64's sequences.
+    /// Larger values are clamped to `MAX_REVERB`
+    pub fn set_reverb(&mut self, level: u8) {
+        if AUDIO_INITIALIZED.load(Ordering::SeqCst) {
+            unsafe { libsm64_sys::sm64_set_reverb(level.min(MAX_REVERB)) }
+        }
+    }
+
+    /// The sequence id of the background music that is playing, `None` if there is none or `audio_init` has not been called
+    pub fn current_music(&self) -> Option<u16> {
+        if !AUDIO_INITIALIZED.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        let seq_id = unsafe { libsm64_sys::sm64_get_current_background_music() };
+        if seq_id == NO_MUSIC {
+            None
+        } else {
+            Some(seq_id)
+        }
+    }
+}
+
+impl Drop for Sm64 {
+    fn drop(&mut self) {
+        *WORLD_BOUNDS.lock().unwrap() = None;
+        *HANDEDNESS.lock().unwrap() = Handedness::Right;
+        *DEBUG_HANDLER.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        INSTANCE.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Configures the creation of an `Sm64`, for when the defaults used by `Sm64::new` are not enough
+///
+/// ```no_run
+/// # use libsm64::*;
+/// let rom = std::fs::File::open("./baserom.us.z64").unwrap();
+/// let sm64 = Sm64Builder::new()
+///     .rom(rom)
+///     .debug_handler(|message| println!("{}", message))
+///     .enable_audio(true)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct Sm64Builder {
+    rom: Option<std::io::Result<Vec<u8>>>,
+    debug_handler: Option<DebugHandler>,
+    region: Option<RomRegion>,
+    enable_audio: bool,
+}
+
+impl Sm64Builder {
+    /// Create a builder with no rom, no debug handler, any supported region, and audio disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The Super Mario 64 rom to extract Mario's texture and animation data from, it is read immediately and any read error is returned by `build`.
+    /// The rom may be in any of the .z64, .v64, or .n64 byte orders
+    pub fn rom<R: Read>(&mut self, rom: R) -> &mut Self {
+        let mut rom_file = BufReader::new(rom);
+        let mut rom_data = Vec::new();
+        self.rom = Some(rom_file.read_to_end(&mut rom_data).map(|_| rom_data));
+        self
+    }
+
+    /// Pass libsm64's debug messages to `handler`, see `Sm64::with_debug_handler`
+    pub fn debug_handler<F: FnMut(&str) + Send + 'static>(&mut self, handler: F) -> &mut Self {
+        self.debug_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Require the rom to be from `region`, `build` returns `Error::UnsupportedRegion` with the rom's actual region if it is a different version
+    pub fn region(&mut self, region: RomRegion) -> &mut Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Call `Sm64::audio_init` once libsm64 is initialized, audio is disabled by default
+    pub fn enable_audio(&mut self, enable: bool) -> &mut Self {
+        self.enable_audio = enable;
+        self
+    }
+
+    /// Create the `Sm64`, the rom and debug handler are moved into it so a builder can only build once
+    pub fn build(&mut self) -> Result<Sm64, Error> {
+        let rom = self.rom.take().ok_or(Error::MissingRom)??;
+
+        let mut sm64 = Sm64::init(RomData::Owned(rom), self.region, self.debug_handler.take())?;
+        if self.enable_audio {
+            sm64.audio_init();
+        }
+
+        Ok(sm64)
+    }
+}
+
+fn find_floor(p: Point3<f32>) -> Option<(f32, libsm64_sys::SM64SurfaceCollisionData)> {
+    let mut floor_surface: *mut libsm64_sys::SM64SurfaceCollisionData = std::ptr::null_mut();
+    let floor = unsafe {
+        libsm64_sys::sm64_surface_find_floor(p.x, p.y, p.z, &mut floor_surface as *mut _)
+    };
+
+    if floor > FLOOR_LOWER_LIMIT && !floor_surface.is_null() {
+        Some((floor, unsafe { *floor_surface }))
+    } else {
+        None
+    }
+}
+
+// The normal of the closest wall within Mario's wall contact radius of `p`
+fn find_wall_normal(p: Point3<f32>) -> Option<Point3<f32>> {
+    let mut collision = libsm64_sys::SM64WallCollisionData {
+        x: p.x,
+        y: p.y,
+        z: p.z,
+        offsetY: WALL_CONTACT_OFFSET_Y,
+        radius: WALL_CONTACT_RADIUS,
+        unused: 0,
+        numWalls: 0,
+        walls: [std::ptr::null_mut(); 4],
+    };
+
+    let num_walls =
+        unsafe { libsm64_sys::sm64_surface_find_wall_collisions(&mut collision as *mut _) };
+
+    if num_walls <= 0 || collision.walls[0].is_null() {
+        return None;
+    }
+
+    let normal = unsafe { (*collision.walls[0]).normal };
+
+    Some(Point3 {
+        x: normal.x,
+        y: normal.y,
+        z: normal.z,
+    })
+}
+
+fn find_floor_height(p: Point3<f32>) -> Option<f32> {
+    let floor = unsafe { libsm64_sys::sm64_surface_find_floor_height(p.x, p.y, p.z) };
+
+    if floor > FLOOR_LOWER_LIMIT {
+        Some(floor)
+    } else {
+        None
+    }
+}
+
+/// The closest triangle hit by `Sm64::raycast`
+#[derive(Copy, Clone, Debug)]
+pub struct RayHit {
+    /// The point where the ray hit the triangle
+    pub point: Point3<f32>,
+    /// The normal of the triangle that was hit
+    pub normal: Point3<f32>,
+    /// The distance along the ray to the hit point
+    pub distance: f32,
+    /// The surface type of the triangle, `None` if the type is not one that sm64 recognizes
+    pub surface: Option<Surface>,
+    /// The terrain of the triangle, `None` if the terrain is not one that sm64 recognizes
+    pub terrain: Option<Terrain>,
 }
 
 /// A instance of Mario that can be controlled
+///
+/// # Setters and `tick`
+///
+/// Every setter, such as `set_action`, `set_position`, `set_velocity`, `take_damage`, or `set_water_level`, writes straight into Mario's state inside libsm64.
+/// Nothing is simulated until the next `tick`, which runs one frame starting from the new state. The `MarioState` returned by `tick`, the `geometry`, and the `events`
+/// always describe the last `tick`, and are not updated by a setter. libsm64 can't refresh them without advancing a frame, so to see a setter's effect call `tick`.
+///
+/// Setters called between the same two ticks are applied in the order they are called, and a later call overwrites an earlier one for the same value.
+/// Setting an action can itself change Mario's velocity, as the engine does when entering a jump, and `take_damage` sets a knockback action.
+/// So call `set_action` and `take_damage` first, then `set_position`, `set_velocity`, and `set_face_angle` to have the final say
 pub struct Mario {
     id: i32,
+    generation: u32,
     geometry: MarioGeometry,
+    events: Vec<MarioEvent>,
+    out_of_bounds_y: f32,
+    out_of_bounds: bool,
+    warps: WarpTable,
+    warp: Option<Surface>,
+    floor: Option<Surface>,
+    action: u32,
+    wall_detection: bool,
+    _not_sync: PhantomData<Cell<()>>,
 }
 
 impl Mario {
     fn new(id: i32) -> Self {
         let geometry = MarioGeometry::new();
-        Self { id, geometry }
+        Self {
+            id,
+            generation: GENERATION.load(Ordering::SeqCst),
+            geometry,
+            events: Vec::new(),
+            out_of_bounds_y: f32::NEG_INFINITY,
+            out_of_bounds: false,
+            warps: WarpTable::new(),
+            warp: None,
+            floor: None,
+            action: 0,
+            wall_detection: false,
+            _not_sync: PhantomData,
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.generation == GENERATION.load(Ordering::SeqCst)
+    }
+
+    /// Fill in `MarioState::wall_normal` during each `tick`. It is disabled by default since finding the wall is an extra collision query each tick
+    pub fn set_wall_detection(&mut self, enabled: bool) {
+        self.wall_detection = enabled;
+    }
+
+    /// The id libsm64 assigned to this Mario, it doesn't change for as long as this `Mario` exists.
+    /// Once the `Mario` is dropped libsm64 may give the same id to a new Mario
+    pub fn id(&self) -> i32 {
+        self.id
     }
 
-    /// Advance the Mario simulation ahead by 1 frame, should be called 30 times per second
+    /// Advance the Mario simulation ahead by 1 frame, should be called `TICK_RATE_HZ` (30) times per second
     pub fn tick(&mut self, input: MarioInput) -> MarioState {
-        let input = input.into();
+        let handedness = *HANDEDNESS.lock().unwrap();
+        let input = handedness.convert_input(input).into();
         let mut state = libsm64_sys::SM64MarioState {
             position: [0.0, 0.0, 0.0],
             velocity: [0.0, 0.0, 0.0],
             faceAngle: 0.0,
             health: 0,
+            action: 0,
+            flags: 0,
+            particleFlags: 0,
+            invincTimer: 0,
+            #[cfg(feature = "counters")]
+            numLives: 0,
+            #[cfg(feature = "counters")]
+            numCoins: 0,
+            #[cfg(feature = "counters")]
+            numStars: 0,
         };
 
+        self.events.clear();
+
+        if !self.is_live() {
+            self.geometry.update(0);
+            return state.into();
+        }
+
+        debug_assert!(SM64.get().is_some(), "Sm64::new() must of been called");
+
         let tris = unsafe {
             let mut geometry: libsm64_sys::SM64MarioGeometryBuffers = (&mut self.geometry).into();
+
+            debug_assert!(
+                !geometry.position.is_null()
+                    && !geometry.normal.is_null()
+                    && !geometry.color.is_null()
+                    && !geometry.uv.is_null(),
+                "Mario's geometry buffers must not be null"
+            );
+            debug_assert_eq!(
+                geometry.numTrianglesUsed as u32,
+                libsm64_sys::SM64_GEO_MAX_TRIANGLES,
+                "Mario's geometry buffers must be large enough for the maximum number of triangles"
+            );
+
             libsm64_sys::sm64_mario_tick(
                 self.id,
                 &input as *const _,
@@ -235,529 +1069,5771 @@ impl Mario {
             geometry.numTrianglesUsed
         };
 
-        self.geometry.num_triangles = tris as usize;
+        debug_assert!(
+            tris as u32 <= libsm64_sys::SM64_GEO_MAX_TRIANGLES,
+            "libsm64 returned more triangles than Mario's geometry buffers can hold"
+        );
 
-        state.into()
-    }
+        let mut state: MarioState = state.into();
 
-    /// Mario's geometry as of the current tick
-    pub fn geometry(&self) -> &MarioGeometry {
-        &self.geometry
-    }
-}
+        if let Some((min, max)) = *WORLD_BOUNDS.lock().unwrap() {
+            let position = Point3 {
+                x: state.position.x.clamp(min.x, max.x),
+                y: state.position.y.clamp(min.y, max.y),
+                z: state.position.z.clamp(min.z, max.z),
+            };
 
-impl Drop for Mario {
-    fn drop(&mut self) {
-        unsafe { libsm64_sys::sm64_mario_delete(self.id) }
+            if position != state.position {
+                // Stop Mario moving further into the bound so he doesn't keep pressing against it
+                let velocity = Point3 {
+                    x: if position.x != state.position.x {
+                        0.0
+                    } else {
+                        state.velocity.x
+                    },
+                    y: if position.y != state.position.y {
+                        0.0
+                    } else {
+                        state.velocity.y
+                    },
+                    z: if position.z != state.position.z {
+                        0.0
+                    } else {
+                        state.velocity.z
+                    },
+                };
+
+                // This tick's geometry was built at the unclamped position, so it is moved along with Mario
+                let offset = Point3 {
+                    x: position.x - state.position.x,
+                    y: position.y - state.position.y,
+                    z: position.z - state.position.z,
+                };
+                for p in &mut self.geometry.position[..tris as usize * 3] {
+                    p.x += offset.x;
+                    p.y += offset.y;
+                    p.z += offset.z;
+                }
+
+                state.position = position;
+                state.velocity = velocity;
+                unsafe {
+                    libsm64_sys::sm64_set_mario_position(
+                        self.id, position.x, position.y, position.z,
+                    );
+                    libsm64_sys::sm64_set_mario_velocity(
+                        self.id, velocity.x, velocity.y, velocity.z,
+                    );
+                }
+            }
+        }
+
+        if handedness == Handedness::Left {
+            self.geometry.mirror(tris as usize);
+        }
+        self.geometry.update(tris as usize);
+
+        if state.position.y < self.out_of_bounds_y {
+            if !self.out_of_bounds {
+                self.out_of_bounds = true;
+                self.kill();
+                self.events.push(MarioEvent::Died);
+            }
+        } else {
+            self.out_of_bounds = false;
+        }
+
+        let floor = find_floor(state.position);
+
+        state.ground_shadow = floor.map(|(height, _)| {
+            let position = Point3 {
+                y: height,
+                ..state.position
+            };
+            // As in the original game the shadow shrinks to half size as Mario rises 600 units above the floor
+            let scale = 1.0 - 0.5 * ((state.position.y - height).max(0.0) / 600.0).min(1.0);
+
+            (position, scale)
+        });
+
+        if self.wall_detection {
+            state.wall_normal = find_wall_normal(state.position);
+        }
+
+        let floor_surface = floor
+            .filter(|(height, _)| state.position.y - height <= FLOOR_CONTACT_DISTANCE)
+            .and_then(|(_, surface)| Surface::from_raw(surface.type_ as u16));
+
+        if self.floor != floor_surface {
+            if let Some(surface) = self.floor {
+                self.events.push(MarioEvent::SurfaceExit { surface });
+            }
+            if let Some(surface) = floor_surface {
+                self.events.push(MarioEvent::SurfaceEnter { surface });
+            }
+        }
+        self.floor = floor_surface;
+
+        let warp = floor_surface.filter(|surface| self.warps.contains(*surface));
+        if let Some(id) = warp {
+            if self.warp != warp {
+                self.events.push(MarioEvent::Warp { id });
+            }
+        }
+        self.warp = warp;
+
+        if self.action != state.action {
+            if let Some(rumble) = Action::from_raw(state.action).and_then(action_rumble) {
+                self.events.push(rumble);
+            }
+        }
+        self.action = state.action;
+
+        handedness.convert_state(state)
     }
-}
 
-/// A dynamic surface that can have its position and rotation updated at runtime, good for moving platforms
-pub struct DynamicSurface {
-    id: u32,
-}
+    /// Force Mario into `action`, the action takes effect on the next `tick`. Setting an action that doesn't fit Mario's situation, such as a swimming
+    /// action out of water, is allowed and the engine will usually move him to a more appropriate action on its own
+    pub fn set_action(&mut self, action: Action) {
+        self.set_action_raw(action.into())
+    }
 
-impl DynamicSurface {
-    fn new(id: u32) -> Self {
-        Self { id }
+    /// Force Mario into an action by its raw `ACT_*` value, for actions not covered by `Action`
+    pub fn set_action_raw(&mut self, action: u32) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_action(self.id, action) }
+        }
     }
 
-    /// Reposition or rotate the surface
-    pub fn transform(&mut self, transform: SurfaceTransform) {
-        unsafe {
-            let transform = transform.into();
-            libsm64_sys::sm64_surface_object_move(self.id, &transform as *const _)
+    /// Set the height of the water surface Mario can swim in, there is no water by default.
+    ///
+    /// **Note:** The new level is checked on the next `tick`, that is when Mario starts swimming if the water is now above him, even in the middle of a jump
+    pub fn set_water_level(&mut self, y: i16) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_water_level(self.id, y as i32) }
         }
     }
-}
 
-impl Drop for DynamicSurface {
-    fn drop(&mut self) {
-        unsafe { libsm64_sys::sm64_surface_object_delete(self.id) }
+    /// Set the height of the poison gas that hurts Mario while his head is below it, there is no gas by default.
+    /// Like the water level it takes effect on the next `tick`
+    pub fn set_gas_level(&mut self, y: i16) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_gas_level(self.id, y as i32) }
+        }
     }
-}
 
-/// Representions a transform that can be applied to a dynamic surface
-#[derive(Copy, Clone, Debug)]
-pub struct SurfaceTransform {
-    /// The x/y/z coordinates of the surface
-    pub position: Point3<f32>,
-    /// The rotation of the surface on each axis, the units should be in degrees
-    pub euler_rotation: Point3<f32>,
-}
+    /// Play `animation` on Mario starting from its first frame.
+    ///
+    /// **Note:** Most of Mario's actions pick their own animation during `tick`, so this is usually paired with `set_action`.
+    /// An animation that doesn't match Mario's action can be replaced on the next `tick` or look wrong for what Mario is doing
+    pub fn set_animation(&mut self, animation: MarioAnimation) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_animation(self.id, animation.into()) }
+        }
+    }
 
-impl From<SurfaceTransform> for libsm64_sys::SM64ObjectTransform {
-    fn from(transform: SurfaceTransform) -> Self {
-        Self {
-            position: [
-                transform.position.x,
-                transform.position.y,
-                transform.position.z,
-            ],
-            eulerRotation: [
-                transform.euler_rotation.x,
-                transform.euler_rotation.y,
-                transform.euler_rotation.z,
-            ],
+    /// Jump to `frame` of Mario's current animation
+    pub fn set_anim_frame(&mut self, frame: i16) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_anim_frame(self.id, frame) }
         }
     }
-}
 
-/// A texture atlas that can be applied to the Mario geometry
-pub struct Texture {
-    /// 8-bit RGBA values
-    pub data: &'static [u8],
-    /// The width of the texture
-    pub width: u32,
-    /// The height of the texture
-    pub height: u32,
-}
+    /// Move Mario to `position` without changing his action or velocity.
+    ///
+    /// **Note:** The position is not validated, if it is outside of the loaded geometry or below the death plane the next `tick` decides what happens to Mario
+    pub fn set_position(&mut self, position: Point3<f32>) {
+        let position = HANDEDNESS.lock().unwrap().convert(position);
 
-/// A point in 3D space
-#[repr(C)]
-#[derive(Debug, Default, Copy, Clone)]
-pub struct Point3<T>
-where
-    T: Copy,
-{
-    pub x: T,
-    pub y: T,
-    pub z: T,
-}
+        if self.is_live() {
+            unsafe {
+                libsm64_sys::sm64_set_mario_position(self.id, position.x, position.y, position.z)
+            }
+        }
+    }
 
-/// A point in 2D space
-#[repr(C)]
-#[derive(Debug, Default, Copy, Clone)]
-pub struct Point2<T>
-where
-    T: Copy,
-{
-    pub x: T,
-    pub y: T,
-}
+    /// Set Mario's velocity on each axis
+    pub fn set_velocity(&mut self, velocity: Point3<f32>) {
+        let velocity = HANDEDNESS.lock().unwrap().convert(velocity);
 
-/// A color
-#[repr(C)]
-#[derive(Debug, Default, Copy, Clone)]
-pub struct Color {
-    pub r: f32,
-    pub g: f32,
-    pub b: f32,
-}
+        if self.is_live() {
+            unsafe {
+                libsm64_sys::sm64_set_mario_velocity(self.id, velocity.x, velocity.y, velocity.z)
+            }
+        }
+    }
 
-/// A level triangle, the main building block of the collision geometry
-#[repr(C)]
-#[derive(Copy, Clone, Debug)]
-pub struct LevelTriangle {
-    /// The type of surface
-    pub kind: Surface,
-    pub force: i16,
-    /// The type of terrain
-    pub terrain: Terrain,
-    /// The verticies of the triangle. Super Mario 64 using integer math for its collision detection expect to have to scale your vertexes appropriately to use them for level geometry
+    /// Set Mario's speed in the direction he is facing, it is applied to his horizontal velocity on the next `tick`.
     ///
-    /// **Note:** The order of the verticies is important, Mario will only collide with the front face of the geometry
-    pub vertices: (Point3<i16>, Point3<i16>, Point3<i16>),
-}
+    /// **Note:** Stationary actions such as standing idle reset the forward velocity to 0, it only has a visible effect while Mario is moving or airborne
+    pub fn set_forward_velocity(&mut self, velocity: f32) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_forward_velocity(self.id, velocity) }
+        }
+    }
 
-/// The input for a frame of Mario's logic
-#[derive(Copy, Clone, Debug, Default)]
-pub struct MarioInput {
-    ///  The position of the camera on the x-axis, used to adjust the movement of mario based on his postion relative to the camera
-    pub cam_look_x: f32,
-    ///  The position of the camera on the z-axis, used to adjust the movement of mario based on his postion relative to the camera
-    pub cam_look_z: f32,
-    /// The input of the analog control on the x-axis (-1.0..1.0)
-    pub stick_x: f32,
-    /// The input of the analog control on the y-axis (-1.0..1.0)
-    pub stick_y: f32,
-    /// Is the A button pressed
-    pub button_a: bool,
-    /// Is the B button pressed
-    pub button_b: bool,
-    /// Is the Z button pressed
-    pub button_z: bool,
-}
+    /// Set the direction Mario is facing in radians, using the same convention as `MarioState::face_angle`.
+    /// The engine stores angles as 16-bit integers with `0x10000` units per full turn, so the angle is truncated to a whole unit
+    pub fn set_face_angle(&mut self, radians: f32) {
+        let radians = match *HANDEDNESS.lock().unwrap() {
+            Handedness::Right => radians,
+            Handedness::Left => -radians,
+        };
+        // libsm64 casts the angle straight to an i16, so it must be wrapped into -PI..PI first
+        let radians = (radians + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+            - std::f32::consts::PI;
 
-impl From<MarioInput> for libsm64_sys::SM64MarioInputs {
-    fn from(input: MarioInput) -> Self {
-        libsm64_sys::SM64MarioInputs {
-            camLookX: input.cam_look_x,
-            camLookZ: input.cam_look_z,
-            stickX: input.stick_x,
-            stickY: input.stick_y,
-            buttonA: input.button_a as u8,
-            buttonB: input.button_b as u8,
-            buttonZ: input.button_z as u8,
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_faceangle(self.id, radians) }
         }
     }
-}
 
-/// Mario's state after a tick of logic
-#[derive(Debug, Default, Copy, Clone)]
-pub struct MarioState {
-    /// The position of Mario in 3D space
-    pub position: Point3<f32>,
-    /// The velocity of Mario on each axis
-    pub velocity: Point3<f32>,
-    /// The direction Mario is facing
-    pub face_angle: f32,
-    /// Mario's current health
-    pub health: i16,
-}
+    /// Damage Mario by a number of wedges of his health meter, each wedge is `0x100` health. Mario is knocked back away from `source` and
+    /// plays his hurt animation on the next `tick`, his health then drains over the following ticks as it does in the original game
+    pub fn take_damage(&mut self, damage: u32, source: Point3<f32>) {
+        let source = HANDEDNESS.lock().unwrap().convert(source);
 
-impl From<libsm64_sys::SM64MarioState> for MarioState {
-    fn from(state: libsm64_sys::SM64MarioState) -> Self {
-        let position = Point3 {
-            x: state.position[0],
-            y: state.position[1],
-            z: state.position[2],
-        };
-        let velocity = Point3 {
-            x: state.velocity[0],
-            y: state.velocity[1],
-            z: state.velocity[2],
-        };
-        MarioState {
-            position,
-            velocity,
-            face_angle: state.faceAngle,
-            health: state.health,
+        if self.is_live() {
+            unsafe {
+                libsm64_sys::sm64_mario_take_damage(
+                    self.id, damage, 0, source.x, source.y, source.z,
+                )
+            }
         }
     }
-}
 
-/// Mario's geometry
-pub struct MarioGeometry {
-    position: Vec<Point3<f32>>,
-    normal: Vec<Point3<f32>>,
-    color: Vec<Color>,
-    uv: Vec<Point2<f32>>,
-    num_triangles: usize,
-}
+    /// Restore a number of wedges of Mario's health meter, the health is restored gradually over the following ticks
+    pub fn heal(&mut self, wedges: u8) {
+        // The heal counter restores a quarter wedge each tick
+        let heal_counter = wedges.saturating_mul(4);
 
-impl MarioGeometry {
-    fn new() -> Self {
-        Self {
-            position: vec![Point3::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
-            normal: vec![Point3::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
-            color: vec![Color::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
-            uv: vec![Point2::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
-            num_triangles: 0,
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_mario_heal(self.id, heal_counter) }
         }
     }
 
-    /// The geometry represented as a series of vertices, every 3 verticies is a new triangle. Includes position, normal, color, and texture coordinates
-    pub fn vertices(&self) -> impl Iterator<Item = MarioVertex> + '_ {
-        let positions = self.position.iter().copied();
-        let normals = self.normal.iter().copied();
-        let color = self.color.iter().copied();
-        let uv = self.uv.iter().copied();
+    /// Kill Mario, his health is drained and he plays a death animation over the following ticks.
+    /// It is still safe to `tick` a dead Mario, the geometry continues to show the death animation and then holds his final pose. Check `MarioState::is_dead` and create a new Mario to respawn
+    pub fn kill(&mut self) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_mario_kill(self.id) }
+        }
+    }
 
-        positions
-            .zip(normals)
-            .zip(color)
-            .zip(uv)
-            .take(self.num_triangles * 3)
-            .map(|(((position, normal), color), uv)| MarioVertex {
-                position,
-                normal,
-                color,
-                uv,
-            })
+    /// Give Mario a cap for `duration_frames` ticks, a duration of 0 uses the game's default duration for the cap.
+    /// If `play_music` is true the cap's music is started, audio must be handled by you for it to be heard
+    pub fn interact_cap(&mut self, cap: Cap, duration_frames: u16, play_music: bool) {
+        if self.is_live() {
+            unsafe {
+                libsm64_sys::sm64_mario_interact_cap(
+                    self.id,
+                    cap as u32,
+                    duration_frames,
+                    play_music as u8,
+                )
+            }
+        }
     }
 
-    /// The geometry represented as a series of triangles. Includes position, normal, color, and texture coordinates
-    pub fn triangles(&self) -> impl Iterator<Item = (MarioVertex, MarioVertex, MarioVertex)> + '_ {
-        let positions = self.position.chunks_exact(3);
-        let normals = self.normal.chunks_exact(3);
-        let color = self.color.chunks_exact(3);
-        let uv = self.uv.chunks_exact(3);
+    /// Extend the duration of Mario's current cap by `frames` ticks
+    pub fn extend_cap(&mut self, frames: u16) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_mario_extend_cap(self.id, frames) }
+        }
+    }
 
-        positions
-            .zip(normals)
-            .zip(color)
-            .zip(uv)
-            .take(self.num_triangles)
-            .map(|(((positions, normals), colors), uvs)| {
-                let a = MarioVertex {
-                    position: positions[0],
-                    normal: normals[0],
-                    color: colors[0],
-                    uv: uvs[0],
-                };
-                let b = MarioVertex {
-                    position: positions[1],
-                    normal: normals[1],
-                    color: colors[1],
-                    uv: uvs[1],
-                };
-                let c = MarioVertex {
-                    position: positions[2],
-                    normal: normals[2],
-                    color: colors[2],
-                    uv: uvs[2],
-                };
-                (a, b, c)
-            })
+    /// Make Mario invincible for `frames` ticks, he flashes and can not be damaged until it expires. A negative or zero value clears his invincibility
+    pub fn set_invincibility(&mut self, frames: i16) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_set_mario_invincibility(self.id, frames.max(0)) }
+        }
     }
 
-    /// The position elements of Mario's verticies
-    pub fn positions(&self) -> &[Point3<f32>] {
-        &self.position[0..self.num_triangles * 3]
+    /// Put Mario back into the state he spawned in at `position`, reusing this instance rather than creating a new Mario.
+    /// His velocity is zeroed, his health is restored to full, he is made idle, and any pending events are cleared.
+    /// Damage or healing from before the reset that had not yet been applied to his health is cancelled.
+    ///
+    /// Like `Sm64::create_mario` an `Error::InvalidMarioPosition` is returned if there is no floor below `position`, Mario is left unchanged in that case
+    pub fn reset(&mut self, position: Point3<f32>) -> Result<(), Error> {
+        if !self.is_live() {
+            return Err(Error::NotInitialized);
+        }
+
+        let sm64_position = HANDEDNESS.lock().unwrap().convert(position);
+        if find_floor_height(sm64_position).is_none() {
+            return Err(Error::InvalidMarioPosition);
+        }
+
+        self.set_position(position);
+        self.set_velocity(Point3::default());
+        self.set_forward_velocity(0.0);
+        self.set_action(Action::Idle);
+        self.set_invincibility(0);
+        unsafe { libsm64_sys::sm64_mario_reset_health(self.id, FULL_HEALTH) }
+
+        self.events.clear();
+        self.out_of_bounds = false;
+        self.warp = None;
+        self.floor = None;
+        self.action = 0;
+
+        Ok(())
     }
 
-    /// The normal elements of Mario's verticies
-    pub fn normals(&self) -> &[Point3<f32>] {
-        &self.normal[0..self.num_triangles * 3]
+    /// Kill Mario when he falls below the provided height during `tick`, emitting a `MarioEvent::Died`. By default this is `f32::NEG_INFINITY` and falling out of the world is left entirely to the engine
+    pub fn set_out_of_bounds_y(&mut self, y: f32) {
+        self.out_of_bounds_y = y;
+        self.out_of_bounds = false;
     }
 
-    /// The color elements of Mario's verticies
-    pub fn colors(&self) -> &[Color] {
-        &self.color[0..self.num_triangles * 3]
+    /// The warps Mario can use, when Mario touches a warp surface in the table a `MarioEvent::Warp` is emitted
+    pub fn set_warp_table(&mut self, warps: WarpTable) {
+        self.warps = warps;
+        self.warp = None;
     }
 
-    /// The texture coordinate elements of Mario's verticies
-    pub fn uvs(&self) -> &[Point2<f32>] {
-        &self.uv[0..self.num_triangles * 3]
+    /// The events that occured during the most recent tick
+    pub fn events(&self) -> &[MarioEvent] {
+        &self.events
+    }
+
+    /// Mario's geometry as of the current tick
+    pub fn geometry(&self) -> &MarioGeometry {
+        &self.geometry
+    }
+
+    /// Mario's geometry as of the current tick interleaved into a single buffer, see `MarioGeometry::interleaved`
+    pub fn interleaved(&mut self) -> &[MarioVertexPacked] {
+        self.geometry.interleaved()
     }
 }
 
-impl<'a> From<&'a mut MarioGeometry> for libsm64_sys::SM64MarioGeometryBuffers {
-    fn from(geo: &'a mut MarioGeometry) -> libsm64_sys::SM64MarioGeometryBuffers {
-        libsm64_sys::SM64MarioGeometryBuffers {
-            position: geo.position.as_mut_ptr() as *mut _,
-            normal: geo.normal.as_mut_ptr() as *mut _,
-            color: geo.color.as_mut_ptr() as *mut _,
-            uv: geo.uv.as_mut_ptr() as *mut _,
-            numTrianglesUsed: geo.position.len() as u16 / 3,
+impl Drop for Mario {
+    fn drop(&mut self) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_mario_delete(self.id) }
         }
     }
 }
 
-/// A vertex that makes up Mario's model
-#[derive(Debug, Copy, Clone)]
-pub struct MarioVertex {
-    /// The position of the vertex
-    pub position: Point3<f32>,
-    /// The normal of the vertex
-    pub normal: Point3<f32>,
-    /// The color of the vertex
-    pub color: Color,
-    /// The texture coordinate of the vertex
-    pub uv: Point2<f32>,
+/// The group an action belongs to, stored in bits 6-8 of the action's value
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ActionGroup {
+    /// Standing still on the ground
+    Stationary,
+    /// Moving along the ground, including sliding and landing
+    Moving,
+    /// In the air, such as jumping or falling
+    Airborne,
+    /// Underwater, including metal Mario walking on the sea floor
+    Submerged,
+    /// Controlled by a cutscene, such as dialog, doors, or death
+    Cutscene,
+    /// Attached to something, such as a pole, a ledge, or hanging from a ceiling
+    Automatic,
+    /// Interacting with an object, such as punching, picking up, or throwing
+    Object,
+    /// A group not used by the original game
+    Unknown,
 }
 
-/// The surface terrain of a triangle
-#[repr(u16)]
-#[derive(Copy, Clone, Debug)]
-pub enum Terrain {
-    Grass = 0x0000,
-    Stone = 0x0001,
-    Snow = 0x0002,
-    Sand = 0x0003,
-    Spooky = 0x0004,
-    Water = 0x0005,
-    Slide = 0x0006,
-    Mask = 0x0007,
+/// A coarse summary of what Mario is doing, useful for deciding how much work to spend on Marios that are far away or off screen
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ActivityLevel {
+    /// Standing still on the ground
+    Idle,
+    /// Moving along the ground or swimming
+    Walking,
+    /// In the air, such as jumping or falling
+    Airborne,
+    /// In a cutscene, attached to something like a pole or ledge, or interacting with an object
+    Interacting,
 }
 
-/// The surface type of a triangle
-#[repr(u16)]
-#[derive(Copy, Clone, Debug)]
-pub enum Surface {
-    Default = 0x0000,
-    Burning = 0x0001,
-    _0004 = 0x0004,
-    Hangable = 0x0005,
-    Slow = 0x0009,
-    DeathPlane = 0x000A,
-    CloseCamera = 0x000B,
-    Water = 0x000D,
-    FlowingWater = 0x000E,
-    Intangible = 0x0012,
-    VerySlippery = 0x0013,
-    Slippery = 0x0014,
-    NotSlippery = 0x0015,
-    TtmVines = 0x0016,
-    MgrMusic = 0x001A,
-    InstantWarp1b = 0x001B,
-    InstantWarp1c = 0x001C,
-    InstantWarp1d = 0x001D,
-    InstantWarp1e = 0x001E,
-    ShallowQuicksand = 0x0021,
-    DeepQuicksand = 0x0022,
-    InstantQuicksand = 0x0023,
-    DeepMovingQuicksand = 0x0024,
-    ShallowMovingQuicksand = 0x0025,
-    Quicksand = 0x0026,
-    MovingQuicksand = 0x0027,
-    WallMisc = 0x0028,
-    NoiseDefault = 0x0029,
-    NoiseSlippery = 0x002A,
-    HorizontalWind = 0x002C,
-    InstantMovingQuicksand = 0x002D,
-    Ice = 0x002E,
-    LookUpWarp = 0x002F,
-    Hard = 0x0030,
-    Warp = 0x0032,
-    TimerStart = 0x0033,
-    TimerEnd = 0x0034,
-    HardSlippery = 0x0035,
-    HardVerySlippery = 0x0036,
-    HardNotSlippery = 0x0037,
-    VerticalWind = 0x0038,
-    BossFightCamera = 0x0065,
-    CameraFreeRoam = 0x0066,
-    Thi3Wallkick = 0x0068,
-    CameraPlatform = 0x0069,
-    CameraMiddle = 0x006E,
-    CameraRotateRight = 0x006F,
-    CameraRotateLeft = 0x0070,
-    CameraBoundary = 0x0072,
-    NoiseVerySlippery73 = 0x0073,
-    NoiseVerySlippery74 = 0x0074,
-    NoiseVerySlippery = 0x0075,
-    NoCamCollision = 0x0076,
-    NoCamCollision77 = 0x0077,
-    NoCamColVerySlippery = 0x0078,
-    NoCamColSlippery = 0x0079,
-    Switch = 0x007A,
-    VanishCapWalls = 0x007B,
-    PaintingWobbleA6 = 0x00A6,
-    PaintingWobbleA7 = 0x00A7,
-    PaintingWobbleA8 = 0x00A8,
-    PaintingWobbleA9 = 0x00A9,
-    PaintingWobbleAA = 0x00AA,
-    PaintingWobbleAB = 0x00AB,
-    PaintingWobbleAC = 0x00AC,
-    PaintingWobbleAD = 0x00AD,
-    PaintingWobbleAE = 0x00AE,
-    PaintingWobbleAF = 0x00AF,
-    PaintingWobbleB0 = 0x00B0,
-    PaintingWobbleB1 = 0x00B1,
-    PaintingWobbleB2 = 0x00B2,
-    PaintingWobbleB3 = 0x00B3,
-    PaintingWobbleB4 = 0x00B4,
-    PaintingWobbleB5 = 0x00B5,
-    PaintingWobbleB6 = 0x00B6,
-    PaintingWobbleB7 = 0x00B7,
-    PaintingWobbleB8 = 0x00B8,
-    PaintingWobbleB9 = 0x00B9,
-    PaintingWobbleBA = 0x00BA,
-    PaintingWobbleBB = 0x00BB,
-    PaintingWobbleBC = 0x00BC,
-    PaintingWobbleBD = 0x00BD,
-    PaintingWobbleBE = 0x00BE,
-    PaintingWobbleBF = 0x00BF,
-    PaintingWobbleC0 = 0x00C0,
-    PaintingWobbleC1 = 0x00C1,
-    PaintingWobbleC2 = 0x00C2,
-    PaintingWobbleC3 = 0x00C3,
-    PaintingWobbleC4 = 0x00C4,
-    PaintingWobbleC5 = 0x00C5,
-    PaintingWobbleC6 = 0x00C6,
-    PaintingWobbleC7 = 0x00C7,
-    PaintingWobbleC8 = 0x00C8,
-    PaintingWobbleC9 = 0x00C9,
-    PaintingWobbleCA = 0x00CA,
-    PaintingWobbleCB = 0x00CB,
-    PaintingWobbleCC = 0x00CC,
-    PaintingWobbleCD = 0x00CD,
-    PaintingWobbleCE = 0x00CE,
-    PaintingWobbleCF = 0x00CF,
-    PaintingWobbleD0 = 0x00D0,
-    PaintingWobbleD1 = 0x00D1,
-    PaintingWobbleD2 = 0x00D2,
-    PaintingWarpD3 = 0x00D3,
-    PaintingWarpD4 = 0x00D4,
-    PaintingWarpD5 = 0x00D5,
-    PaintingWarpD6 = 0x00D6,
-    PaintingWarpD7 = 0x00D7,
-    PaintingWarpD8 = 0x00D8,
-    PaintingWarpD9 = 0x00D9,
-    PaintingWarpDA = 0x00DA,
-    PaintingWarpDB = 0x00DB,
-    PaintingWarpDC = 0x00DC,
-    PaintingWarpDD = 0x00DD,
-    PaintingWarpDE = 0x00DE,
-    PaintingWarpDF = 0x00DF,
-    PaintingWarpE0 = 0x00E0,
-    PaintingWarpE1 = 0x00E1,
-    PaintingWarpE2 = 0x00E2,
-    PaintingWarpE3 = 0x00E3,
-    PaintingWarpE4 = 0x00E4,
-    PaintingWarpE5 = 0x00E5,
-    PaintingWarpE6 = 0x00E6,
-    PaintingWarpE7 = 0x00E7,
-    PaintingWarpE8 = 0x00E8,
-    PaintingWarpE9 = 0x00E9,
-    PaintingWarpEA = 0x00EA,
-    PaintingWarpEB = 0x00EB,
-    PaintingWarpEC = 0x00EC,
-    PaintingWarpED = 0x00ED,
-    PaintingWarpEE = 0x00EE,
-    PaintingWarpEF = 0x00EF,
-    PaintingWarpF0 = 0x00F0,
-    PaintingWarpF1 = 0x00F1,
-    PaintingWarpF2 = 0x00F2,
-    PaintingWarpF3 = 0x00F3,
-    TtcPainting1 = 0x00F4,
-    TtcPainting2 = 0x00F5,
-    TtcPainting3 = 0x00F6,
-    PaintingWarpF7 = 0x00F7,
-    PaintingWarpF8 = 0x00F8,
-    PaintingWarpF9 = 0x00F9,
-    PaintingWarpFA = 0x00FA,
-    PaintingWarpFB = 0x00FB,
-    PaintingWarpFC = 0x00FC,
-    WobblingWarp = 0x00FD,
-    Trapdoor = 0x00FF,
+bitflags::bitflags! {
+    /// Mario's flags, the values match the `MARIO_*` flags of Super Mario 64. Bits without a name are kept as they were reported by the engine
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct MarioFlags: u32 {
+        const NORMAL_CAP = 0x0000_0001;
+        const VANISH_CAP = 0x0000_0002;
+        const METAL_CAP = 0x0000_0004;
+        const WING_CAP = 0x0000_0008;
+        const CAP_ON_HEAD = 0x0000_0010;
+        const CAP_IN_HAND = 0x0000_0020;
+        const METAL_SHOCK = 0x0000_0040;
+        const TELEPORTING = 0x0000_0080;
+        const ACTION_SOUND_PLAYED = 0x0001_0000;
+        const MARIO_SOUND_PLAYED = 0x0002_0000;
+        const PUNCHING = 0x0010_0000;
+        const KICKING = 0x0020_0000;
+        const TRIPPING = 0x0040_0000;
+    }
 }
 
-#[test]
-fn basic_loading() {
-    let rom = std::env::var("SM64_ROM_PATH")
-        .expect("Path to SM64 rom must be proivided in 'SM64_ROM_PATH' env var");
-    let rom = std::fs::File::open(rom).unwrap();
-    let mut sm64 = Sm64::new(rom).unwrap();
-    let mario = sm64.create_mario(1, 2, 3);
+impl MarioFlags {
+    /// Returns true if Mario is wearing the wing cap
+    pub fn has_wing_cap(&self) -> bool {
+        self.contains(MarioFlags::WING_CAP)
+    }
 
-    match mario {
-        Err(Error::InvalidMarioPosition) => (),
-        _ => panic!("Expected InvalidMarioPosition error"),
+    /// Returns true if Mario is wearing the metal cap
+    pub fn has_metal_cap(&self) -> bool {
+        self.contains(MarioFlags::METAL_CAP)
+    }
+
+    /// Returns true if Mario is wearing the vanish cap
+    pub fn has_vanish_cap(&self) -> bool {
+        self.contains(MarioFlags::VANISH_CAP)
     }
 }
 
-#[test]
-fn correct_repr() {
-    assert_eq!(
-        std::mem::size_of::<LevelTriangle>(),
-        std::mem::size_of::<libsm64_sys::SM64Surface>()
-    );
+bitflags::bitflags! {
+    /// The caps Mario is wearing, the values match the `MARIO_*_CAP` flags of Super Mario 64
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct CapFlags: u32 {
+        const NORMAL = Cap::Normal as u32;
+        const VANISH = Cap::Vanish as u32;
+        const METAL = Cap::Metal as u32;
+        const WING = Cap::Wing as u32;
+    }
+}
 
-    let tri = LevelTriangle {
-        kind: Surface::Default,
-        force: 333,
-        terrain: Terrain::Grass,
-        vertices: (
-            Point3 { x: 1, y: 2, z: 3 },
-            Point3 { x: 4, y: 5, z: 6 },
-            Point3 { x: 7, y: 8, z: 9 },
+impl From<Cap> for CapFlags {
+    fn from(cap: Cap) -> Self {
+        CapFlags::from_bits_truncate(cap as u32)
+    }
+}
+
+/// A cap Mario can wear, the values match the `MARIO_*_CAP` flags of Super Mario 64
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cap {
+    Normal = 0x0000_0001,
+    Vanish = 0x0000_0002,
+    Metal = 0x0000_0004,
+    Wing = 0x0000_0008,
+}
+
+// Pack a sound into the bits sm64's sound functions expect, mirroring the `SOUND_ARG_LOAD` macro of Super Mario 64
+const fn sound_arg_load(
+    bank: u32,
+    play_flags: u32,
+    sound_id: u32,
+    priority: u32,
+    flags: u32,
+) -> u32 {
+    (bank << 28) | (play_flags << 24) | (sound_id << 16) | (priority << 8) | (flags << 4) | 1
+}
+
+/// A sound effect that can be played with `Sm64::play_sound`, the values match the `SOUND_*` constants of Super Mario 64
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Sound {
+    MarioYahWahHoo = sound_arg_load(2, 4, 0x00, 0x80, 8),
+    MarioHooHoo = sound_arg_load(2, 4, 0x03, 0x80, 8),
+    MarioYahoo = sound_arg_load(2, 4, 0x04, 0x80, 8),
+    MarioUh = sound_arg_load(2, 4, 0x05, 0x80, 8),
+    MarioHrmm = sound_arg_load(2, 4, 0x06, 0x80, 8),
+    MarioWah2 = sound_arg_load(2, 4, 0x07, 0x80, 8),
+    MarioWhoa = sound_arg_load(2, 4, 0x08, 0xC0, 8),
+    MarioEeuh = sound_arg_load(2, 4, 0x09, 0x80, 8),
+    MarioAttacked = sound_arg_load(2, 4, 0x0A, 0xFF, 8),
+    MarioOoof = sound_arg_load(2, 4, 0x0B, 0x80, 8),
+    MarioHereWeGo = sound_arg_load(2, 4, 0x0C, 0x80, 8),
+    MarioYawning = sound_arg_load(2, 4, 0x0D, 0x80, 8),
+    MarioWaaaooow = sound_arg_load(2, 4, 0x10, 0xC0, 8),
+    MarioHaha = sound_arg_load(2, 4, 0x11, 0x80, 8),
+}
+
+impl From<Sound> for u32 {
+    fn from(sound: Sound) -> Self {
+        sound as u32
+    }
+}
+
+/// A music sequence in the rom that can be played with `Sm64::play_music`, the values match the `SEQ_*` ids of Super Mario 64
+#[repr(u16)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SeqId {
+    SoundPlayer = 0x00,
+    EventCutsceneCollectStar = 0x01,
+    MenuTitleScreen = 0x02,
+    LevelGrass = 0x03,
+    LevelInsideCastle = 0x04,
+    LevelWater = 0x05,
+    LevelHot = 0x06,
+    LevelBossKoopa = 0x07,
+    LevelSnow = 0x08,
+    LevelSlide = 0x09,
+    LevelSpooky = 0x0A,
+    EventPiranhaPlant = 0x0B,
+    LevelUnderground = 0x0C,
+    MenuStarSelect = 0x0D,
+    EventPowerup = 0x0E,
+    EventMetalCap = 0x0F,
+    EventKoopaMessage = 0x10,
+    LevelKoopaRoad = 0x11,
+    EventHighScore = 0x12,
+    EventMerryGoRound = 0x13,
+    EventRace = 0x14,
+    EventCutsceneStarSpawn = 0x15,
+    EventBoss = 0x16,
+    EventCutsceneCollectKey = 0x17,
+    EventEndlessStairs = 0x18,
+    LevelBossKoopaFinal = 0x19,
+    EventCutsceneCredits = 0x1A,
+    EventSolvePuzzle = 0x1B,
+    EventToadMessage = 0x1C,
+    EventPeachMessage = 0x1D,
+    EventCutsceneIntro = 0x1E,
+    EventCutsceneVictory = 0x1F,
+    EventCutsceneEnding = 0x20,
+    MenuFileSelect = 0x21,
+    EventCutsceneLakitu = 0x22,
+}
+
+impl From<SeqId> for u16 {
+    fn from(seq_id: SeqId) -> Self {
+        seq_id as u16
+    }
+}
+
+/// An event that occured during a tick of Mario's logic
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MarioEvent {
+    /// Mario fell below the height set with `Mario::set_out_of_bounds_y` and was killed
+    Died,
+    /// Mario stepped onto a warp surface registered in his `WarpTable`, it is up to you to move him to its destination
+    Warp {
+        /// The surface type of the warp
+        id: Surface,
+    },
+    /// Mario started standing on a floor of a new surface type
+    SurfaceEnter {
+        /// The surface type of the new floor
+        surface: Surface,
+    },
+    /// Mario stopped standing on a floor of this surface type, either by leaving the ground or stepping onto a different surface type. Always emitted before the matching `SurfaceEnter`
+    SurfaceExit {
+        /// The surface type of the previous floor
+        surface: Surface,
+    },
+    /// Mario started an action that rumbles the controller in the Shindou version of the game, such as landing a ground pound, being knocked back, or being shocked.
+    /// It is synthesized from Mario's action changes so it works with any rom, the durations and strengths approximate Shindou's
+    Rumble {
+        /// How many ticks to rumble for
+        ticks: u8,
+        /// How strongly to rumble, from `0.0` to `1.0`
+        strength: f32,
+    },
+}
+
+// The rumble Shindou queues when Mario starts `action`
+fn action_rumble(action: Action) -> Option<MarioEvent> {
+    let (ticks, strength) = match action {
+        Action::GroundPoundLand
+        | Action::HardBackwardGroundKb
+        | Action::HardForwardGroundKb
+        | Action::HardBackwardAirKb
+        | Action::HardForwardAirKb
+        | Action::LavaBoost => (5, 0.8),
+        Action::BackwardGroundKb
+        | Action::ForwardGroundKb
+        | Action::BackwardAirKb
+        | Action::ForwardAirKb
+        | Action::BackwardWaterKb
+        | Action::ForwardWaterKb
+        | Action::ThrownBackward
+        | Action::TripleJumpLand
+        | Action::LavaBoostLand => (5, 0.4),
+        Action::SoftBackwardGroundKb | Action::SoftForwardGroundKb => (5, 0.2),
+        Action::Squished => (10, 0.8),
+        Action::Shocked | Action::WaterShocked => (70, 0.6),
+        _ => return None,
+    };
+
+    Some(MarioEvent::Rumble { ticks, strength })
+}
+
+/// The destinations of the warp surfaces in a level. The original game stored these separately from the collision geometry so they must be provided by you
+#[derive(Clone, Debug, Default)]
+pub struct WarpTable {
+    destinations: HashMap<Surface, Point3<f32>>,
+}
+
+impl WarpTable {
+    /// Create an empty warp table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the destination of a warp, surfaces that are not warps are ignored
+    pub fn insert(&mut self, id: Surface, destination: Point3<f32>) {
+        if id.is_warp() {
+            self.destinations.insert(id, destination);
+        }
+    }
+
+    /// Remove a warp, returning its destination
+    pub fn remove(&mut self, id: Surface) -> Option<Point3<f32>> {
+        self.destinations.remove(&id)
+    }
+
+    /// The destination of a warp
+    pub fn destination(&self, id: Surface) -> Option<Point3<f32>> {
+        self.destinations.get(&id).copied()
+    }
+
+    /// Returns true if the warp has a destination
+    pub fn contains(&self, id: Surface) -> bool {
+        self.destinations.contains_key(&id)
+    }
+}
+
+/// Converts elapsed time into the number of ticks Mario should be advanced by, keeping the simulation at `TICK_RATE_HZ` independent of your frame rate.
+///
+/// Gravity and the rest of Mario's physics are literals compiled into the C core, such as the fall speed each action subtracts every frame, and libsm64 has no way to adjust them.
+/// Instead the simulation can be slowed down or sped up by scaling time with `set_time_scale`
+#[derive(Copy, Clone, Debug)]
+pub struct TickClock {
+    accumulator: Duration,
+    time_scale: f32,
+}
+
+impl TickClock {
+    /// Create a clock running at normal speed
+    pub fn new() -> Self {
+        Self {
+            accumulator: Duration::ZERO,
+            time_scale: 1.0,
+        }
+    }
+
+    /// Scale the rate time passes, 0.5 runs the simulation at half speed. Negative, NaN, and infinite values are treated as 0
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = if time_scale.is_finite() {
+            time_scale.max(0.0)
+        } else {
+            0.0
+        };
+    }
+
+    /// The current time scale
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Advance the clock by the time elapsed since the last call, returning the number of times `Mario::tick` should be called
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed.mul_f32(self.time_scale);
+
+        let mut ticks = 0;
+        while self.accumulator >= TICK_DURATION {
+            self.accumulator -= TICK_DURATION;
+            ticks += 1;
+        }
+
+        ticks
+    }
+
+    /// How far the clock is into the next tick from 0.0 to 1.0, useful for interpolating between the geometry of the previous and current tick
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / TICK_DURATION.as_secs_f32()
+    }
+}
+
+impl Default for TickClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A headless fixed-timestep driver owning an `Sm64` and a single `Mario`, each `step` advances the simulation by exactly one `TICK_DURATION`.
+/// Useful when there is no real time to keep up with, such as tests, replays, or training agents
+pub struct Simulation {
+    mario: Mario,
+    sm64: Sm64,
+    state: MarioState,
+    frame: u64,
+}
+
+impl Simulation {
+    /// Start a simulation of `mario`, the level geometry should already be loaded into `sm64`
+    pub fn new(sm64: Sm64, mario: Mario) -> Self {
+        Self {
+            mario,
+            sm64,
+            state: MarioState::default(),
+            frame: 0,
+        }
+    }
+
+    /// Advance the simulation by one tick with `input`, returning Mario's new state
+    pub fn step(&mut self, input: MarioInput) -> &MarioState {
+        self.state = self.mario.tick(input);
+        self.frame += 1;
+
+        &self.state
+    }
+
+    /// Mario's geometry as of the last `step`
+    pub fn observe(&self) -> &MarioGeometry {
+        self.mario.geometry()
+    }
+
+    /// Mario's state as of the last `step`, the default state before the first `step`
+    pub fn state(&self) -> &MarioState {
+        &self.state
+    }
+
+    /// The number of times `step` has been called
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// The amount of game time that has been simulated, `frame` ticks of `TICK_DURATION`
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos((TICK_DURATION.as_nanos() as u64).saturating_mul(self.frame))
+    }
+
+    /// The `Sm64` being simulated, for changing the level between steps
+    pub fn sm64_mut(&mut self) -> &mut Sm64 {
+        &mut self.sm64
+    }
+
+    /// The `Mario` being simulated, for calls such as `Mario::set_position` between steps.
+    /// Calling `Mario::tick` directly is not counted by `frame`
+    pub fn mario_mut(&mut self) -> &mut Mario {
+        &mut self.mario
+    }
+
+    /// End the simulation, returning the `Sm64` and `Mario`
+    pub fn into_inner(self) -> (Sm64, Mario) {
+        (self.sm64, self.mario)
+    }
+}
+
+/// A complete level definition: the static geometry, the dynamic surfaces, and where Mario spawns.
+/// With the `serde` feature it can be saved to and loaded from a JSON document
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Level {
+    /// The static level geometry, see `Sm64::load_level_geometry`
+    pub geometry: Vec<LevelTriangle>,
+    /// The dynamic surfaces to create along with their starting transforms
+    pub dynamic_surfaces: Vec<DynamicSurfaceDef>,
+    /// The point Mario spawns at, it must be above a surface
+    pub spawn: Point3<i16>,
+}
+
+/// The definition of a dynamic surface in a `Level`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DynamicSurfaceDef {
+    /// The geometry of the surface, relative to its transform
+    pub geometry: Vec<LevelTriangle>,
+    /// The starting transform of the surface
+    pub transform: SurfaceTransform,
+}
+
+/// The objects created by `Level::apply`, dropping them removes them from the simulation
+pub struct LoadedLevel {
+    /// Mario at the level's spawn point
+    pub mario: Mario,
+    /// The level's dynamic surfaces in the same order as `Level::dynamic_surfaces`
+    pub dynamic_surfaces: Vec<DynamicSurface>,
+}
+
+impl Level {
+    /// Load the static geometry into `sm64`, replacing any that was already loaded, then create the dynamic surfaces and Mario
+    pub fn apply(&self, sm64: &mut Sm64) -> Result<LoadedLevel, Error> {
+        sm64.load_level_geometry(&self.geometry);
+
+        let dynamic_surfaces = self
+            .dynamic_surfaces
+            .iter()
+            .map(|surface| sm64.create_dynamic_surface(&surface.geometry, surface.transform))
+            .collect();
+        let mario = sm64.create_mario(self.spawn.x, self.spawn.y, self.spawn.z)?;
+
+        Ok(LoadedLevel {
+            mario,
+            dynamic_surfaces,
+        })
+    }
+
+    /// Write the level to `writer` as JSON
+    #[cfg(feature = "serde")]
+    pub fn save<W: std::io::Write>(&self, writer: W) -> Result<(), Error> {
+        serde_json::to_writer(writer, self).map_err(|err| Error::Io(err.into()))
+    }
+
+    /// Read a level that was written by `save`
+    #[cfg(feature = "serde")]
+    pub fn load<R: Read>(reader: R) -> Result<Self, Error> {
+        serde_json::from_reader(BufReader::new(reader)).map_err(|err| Error::Io(err.into()))
+    }
+}
+
+/// A dynamic surface that can have its position and rotation updated at runtime, good for moving platforms
+///
+/// Like the static level geometry, every Mario collides with every dynamic surface
+pub struct DynamicSurface {
+    id: u32,
+    generation: u32,
+    transform: SurfaceTransform,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl DynamicSurface {
+    fn new(id: u32, transform: SurfaceTransform) -> Self {
+        Self {
+            id,
+            generation: GENERATION.load(Ordering::SeqCst),
+            transform,
+            _not_sync: PhantomData,
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.generation == GENERATION.load(Ordering::SeqCst)
+    }
+
+    /// The id of the surface object libsm64 assigned to this surface. It only changes when `set_geometry` replaces the surface object,
+    /// and once the `DynamicSurface` is dropped libsm64 may give the same id to a new surface
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Reposition or rotate the surface.
+    ///
+    /// libsm64 works out how far the surface moved since the previous call, and Mario is carried by that much on his next `tick` when he is standing on it.
+    /// To carry Mario smoothly call `transform` exactly once per frame, before calling `Mario::tick`. Calling it several times in one frame only carries Mario by the last change
+    pub fn transform(&mut self, transform: SurfaceTransform) {
+        self.transform = transform;
+
+        if !self.is_live() {
+            return;
+        }
+
+        unsafe {
+            let transform = transform.into();
+            libsm64_sys::sm64_surface_object_move(self.id, &transform as *const _)
+        }
+    }
+
+    /// Replace the triangles of the surface, keeping its current transform.
+    ///
+    /// libsm64 has no way to change the triangles of an existing surface object, so a new surface object is created with the new geometry before the old one is deleted.
+    /// There is never a moment where neither exists, and Mario's floor is found again on his next `tick`, so a Mario standing on the surface stays on it
+    pub fn set_geometry(&mut self, geometry: &[LevelTriangle]) {
+        debug_assert!(
+            geometry.len() <= u32::MAX as usize,
+            "Too many triangles in dynamic surface"
+        );
+
+        if !self.is_live() {
+            return;
+        }
+
+        let id = unsafe {
+            let surface_object = libsm64_sys::SM64SurfaceObject {
+                transform: self.transform.into(),
+                surfaceCount: geometry.len() as u32,
+                surfaces: geometry.as_ptr() as *mut _,
+            };
+            libsm64_sys::sm64_surface_object_create(&surface_object as *const _)
+        };
+
+        unsafe { libsm64_sys::sm64_surface_object_delete(self.id) }
+        self.id = id;
+    }
+
+    /// The transform the surface was created with or most recently given to `transform`
+    pub fn current_transform(&self) -> SurfaceTransform {
+        self.transform
+    }
+}
+
+impl Drop for DynamicSurface {
+    fn drop(&mut self) {
+        if self.is_live() {
+            unsafe { libsm64_sys::sm64_surface_object_delete(self.id) }
+        }
+    }
+}
+
+/// Builds a `LevelTriangle` for the less common cases, such as triangles with a force. By default the triangle is `Surface::Default` and `Terrain::Grass` with no force
+#[derive(Copy, Clone, Debug)]
+pub struct LevelTriangleBuilder {
+    triangle: LevelTriangle,
+}
+
+impl LevelTriangleBuilder {
+    /// Start building a triangle from its verticies
+    pub fn new(v0: Point3<i16>, v1: Point3<i16>, v2: Point3<i16>) -> Self {
+        Self {
+            triangle: LevelTriangle::new(Surface::Default, Terrain::Grass, v0, v1, v2),
+        }
+    }
+
+    /// Set the type of surface
+    pub fn kind(&mut self, kind: Surface) -> &mut Self {
+        self.triangle.kind = kind;
+        self
+    }
+
+    /// Set the type of terrain
+    pub fn terrain(&mut self, terrain: Terrain) -> &mut Self {
+        self.triangle.terrain = terrain;
+        self
+    }
+
+    /// Set the raw force of the surface
+    pub fn force(&mut self, force: i16) -> &mut Self {
+        self.triangle.force = force;
+        self
+    }
+
+    /// Set the force of the surface from a direction and strength
+    pub fn surface_force(&mut self, force: SurfaceForce) -> &mut Self {
+        self.triangle.force = force.to_raw();
+        self
+    }
+
+    /// Create the triangle
+    pub fn build(&self) -> LevelTriangle {
+        self.triangle
+    }
+}
+
+/// Collects the static geometry and dynamic surfaces of a level so they can be loaded together, each dynamic surface is identified by a tag of your choosing
+pub struct LevelBuilder<K> {
+    static_geometry: Vec<LevelTriangle>,
+    dynamic_surfaces: Vec<(K, Vec<LevelTriangle>, SurfaceTransform)>,
+}
+
+impl<K: Eq + Hash> LevelBuilder<K> {
+    /// Create an empty level
+    pub fn new() -> Self {
+        Self {
+            static_geometry: Vec::new(),
+            dynamic_surfaces: Vec::new(),
+        }
+    }
+
+    /// Add triangles to the static level geometry
+    pub fn add_static_geometry(&mut self, geometry: &[LevelTriangle]) -> &mut Self {
+        self.static_geometry.extend_from_slice(geometry);
+        self
+    }
+
+    /// Add a dynamic surface with its initial transform, a surface added with the same tag as an earlier one will replace it
+    pub fn add_dynamic_surface(
+        &mut self,
+        tag: K,
+        geometry: Vec<LevelTriangle>,
+        transform: SurfaceTransform,
+    ) -> &mut Self {
+        self.dynamic_surfaces.retain(|(t, _, _)| *t != tag);
+        self.dynamic_surfaces.push((tag, geometry, transform));
+        self
+    }
+
+    /// Load the static geometry, replacing any previously loaded static geometry, and create each of the dynamic surfaces
+    pub fn build(self, sm64: &mut Sm64) -> HashMap<K, DynamicSurface> {
+        sm64.load_level_geometry(&self.static_geometry);
+
+        self.dynamic_surfaces
+            .into_iter()
+            .map(|(tag, geometry, transform)| {
+                let surface = sm64.create_dynamic_surface(&geometry, transform);
+                (tag, surface)
+            })
+            .collect()
+    }
+}
+
+impl<K: Eq + Hash> Default for LevelBuilder<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Representions a transform that can be applied to a dynamic surface
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SurfaceTransform {
+    /// The x/y/z coordinates of the surface
+    pub position: Point3<f32>,
+    /// The rotation of the surface on each axis, the units should be in degrees
+    pub euler_rotation: Point3<f32>,
+}
+
+#[cfg(feature = "glam")]
+impl SurfaceTransform {
+    /// The rotation of the surface as a quaternion. Like Super Mario 64's objects the rotation is applied around the z-axis, then the x-axis, then the y-axis
+    pub fn rotation(&self) -> glam::Quat {
+        glam::Quat::from_euler(
+            glam::EulerRot::YXZ,
+            self.euler_rotation.y.to_radians(),
+            self.euler_rotation.x.to_radians(),
+            self.euler_rotation.z.to_radians(),
+        )
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<SurfaceTransform> for glam::Affine3A {
+    fn from(transform: SurfaceTransform) -> Self {
+        glam::Affine3A::from_rotation_translation(transform.rotation(), transform.position.into())
+    }
+}
+
+/// Surfaces can't be scaled, so any scale in the affine transform is discarded
+#[cfg(feature = "glam")]
+impl From<glam::Affine3A> for SurfaceTransform {
+    fn from(affine: glam::Affine3A) -> Self {
+        let (_, rotation, translation) = affine.to_scale_rotation_translation();
+        let (y, x, z) = rotation.to_euler(glam::EulerRot::YXZ);
+
+        SurfaceTransform {
+            position: translation.into(),
+            euler_rotation: Point3 {
+                x: x.to_degrees(),
+                y: y.to_degrees(),
+                z: z.to_degrees(),
+            },
+        }
+    }
+}
+
+impl From<SurfaceTransform> for libsm64_sys::SM64ObjectTransform {
+    fn from(transform: SurfaceTransform) -> Self {
+        Self {
+            position: [
+                transform.position.x,
+                transform.position.y,
+                transform.position.z,
+            ],
+            eulerRotation: [
+                transform.euler_rotation.x,
+                transform.euler_rotation.y,
+                transform.euler_rotation.z,
+            ],
+        }
+    }
+}
+
+/// A texture atlas that can be applied to the Mario geometry
+pub struct Texture {
+    /// 8-bit RGBA values
+    pub data: &'static [u8],
+    /// The width of the texture
+    pub width: u32,
+    /// The height of the texture
+    pub height: u32,
+}
+
+#[cfg(feature = "image")]
+impl Texture {
+    /// Copy the texture into an image
+    pub fn to_image(&self) -> image::RgbaImage {
+        image::RgbaImage::from_raw(self.width, self.height, self.data.to_vec())
+            .expect("Texture data must be width * height RGBA pixels")
+    }
+
+    /// Save the texture as a PNG at `path`
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.to_image()
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(std::io::Error::other)
+    }
+}
+
+/// A point in 3D space
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point3<T>
+where
+    T: Copy,
+{
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// A point in 2D space
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point2<T>
+where
+    T: Copy,
+{
+    pub x: T,
+    pub y: T,
+}
+
+#[cfg(feature = "mint")]
+impl<T: Copy> From<mint::Point3<T>> for Point3<T> {
+    fn from(p: mint::Point3<T>) -> Self {
+        Point3 {
+            x: p.x,
+            y: p.y,
+            z: p.z,
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl<T: Copy> From<Point3<T>> for mint::Point3<T> {
+    fn from(p: Point3<T>) -> Self {
+        mint::Point3 {
+            x: p.x,
+            y: p.y,
+            z: p.z,
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl<T: Copy> From<mint::Point2<T>> for Point2<T> {
+    fn from(p: mint::Point2<T>) -> Self {
+        Point2 { x: p.x, y: p.y }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl<T: Copy> From<Point2<T>> for mint::Point2<T> {
+    fn from(p: Point2<T>) -> Self {
+        mint::Point2 { x: p.x, y: p.y }
+    }
+}
+
+// Each of these is `repr(C)` and made up entirely of `f32`s, so they have no padding and every bit pattern is valid
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Point3<f32> {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Point3<f32> {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Point2<f32> {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Point2<f32> {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Color {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Color {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for MarioVertex {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for MarioVertex {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for MarioVertexPacked {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for MarioVertexPacked {}
+
+#[cfg(feature = "glam")]
+impl From<glam::Vec3> for Point3<f32> {
+    fn from(v: glam::Vec3) -> Self {
+        Point3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Point3<f32>> for glam::Vec3 {
+    fn from(p: Point3<f32>) -> Self {
+        glam::Vec3::new(p.x, p.y, p.z)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Vec2> for Point2<f32> {
+    fn from(v: glam::Vec2) -> Self {
+        Point2 { x: v.x, y: v.y }
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Point2<f32>> for glam::Vec2 {
+    fn from(p: Point2<f32>) -> Self {
+        glam::Vec2::new(p.x, p.y)
+    }
+}
+
+/// A color
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+/// A level triangle, the main building block of the collision geometry
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LevelTriangle {
+    /// The type of surface
+    pub kind: Surface,
+    pub force: i16,
+    /// The type of terrain
+    pub terrain: Terrain,
+    /// The verticies of the triangle. Super Mario 64 using integer math for its collision detection expect to have to scale your vertexes appropriately to use them for level geometry
+    ///
+    /// **Note:** The order of the verticies is important, Mario will only collide with the front face of the geometry
+    pub vertices: (Point3<i16>, Point3<i16>, Point3<i16>),
+}
+
+impl LevelTriangle {
+    /// Create a triangle with no force
+    pub fn new(
+        kind: Surface,
+        terrain: Terrain,
+        v0: Point3<i16>,
+        v1: Point3<i16>,
+        v2: Point3<i16>,
+    ) -> Self {
+        LevelTriangle {
+            kind,
+            force: 0,
+            terrain,
+            vertices: (v0, v1, v2),
+        }
+    }
+
+    /// Split a quad into two triangles, the corners must be in order around the quad with the same winding a single triangle facing the same way would use
+    pub fn quad(kind: Surface, terrain: Terrain, corners: [Point3<i16>; 4]) -> [Self; 2] {
+        let [a, b, c, d] = corners;
+
+        [
+            Self::new(kind, terrain, a, b, c),
+            Self::new(kind, terrain, a, c, d),
+        ]
+    }
+
+    /// Create a `HorizontalWind` triangle that pushes Mario in `direction` radians
+    pub fn horizontal_wind(
+        vertices: (Point3<i16>, Point3<i16>, Point3<i16>),
+        direction: f32,
+        strength: u8,
+    ) -> Self {
+        let force = SurfaceForce {
+            direction,
+            strength,
+        };
+
+        LevelTriangle {
+            kind: Surface::HorizontalWind,
+            force: force.to_raw(),
+            terrain: Terrain::Grass,
+            vertices,
+        }
+    }
+
+    /// Create a triangle from double precision verticies, each coordinate is multiplied by `scale`, rounded, and clamped to the range of an `i16`.
+    /// Returns `Error::DegenerateTriangle` if the quantized verticies have no area, such as when they all round to the same point
+    pub fn from_f64(
+        kind: Surface,
+        terrain: Terrain,
+        vertices: [Point3<f64>; 3],
+        scale: f64,
+    ) -> Result<Self, Error> {
+        let quantize = |v: f64| (v * scale).round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+        let convert = |p: Point3<f64>| Point3 {
+            x: quantize(p.x),
+            y: quantize(p.y),
+            z: quantize(p.z),
+        };
+
+        let [a, b, c] = vertices.map(convert);
+
+        let ab = [
+            b.x as i64 - a.x as i64,
+            b.y as i64 - a.y as i64,
+            b.z as i64 - a.z as i64,
+        ];
+        let ac = [
+            c.x as i64 - a.x as i64,
+            c.y as i64 - a.y as i64,
+            c.z as i64 - a.z as i64,
+        ];
+        let cross = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+
+        if cross == [0, 0, 0] {
+            return Err(Error::DegenerateTriangle);
+        }
+
+        Ok(LevelTriangle {
+            kind,
+            force: 0,
+            terrain,
+            vertices: (a, b, c),
+        })
+    }
+
+    /// Create a triangle from verticies in your world units, converted to sm64 units with `scale`.
+    /// Returns `Error::CoordinateOutOfRange` if a vertex is too far from the origin to be represented, or `Error::DegenerateTriangle` if the converted verticies have no area
+    pub fn from_world(
+        kind: Surface,
+        terrain: Terrain,
+        vertices: [Point3<f32>; 3],
+        scale: LevelScale,
+    ) -> Result<Self, Error> {
+        let [a, b, c] = vertices;
+        let convert = |p: Point3<f32>| -> Result<Point3<f64>, Error> {
+            let p = scale.world_to_sm64(p)?;
+            Ok(Point3 {
+                x: p.x as f64,
+                y: p.y as f64,
+                z: p.z as f64,
+            })
+        };
+
+        Self::from_f64(kind, terrain, [convert(a)?, convert(b)?, convert(c)?], 1.0)
+    }
+
+    /// Decode the direction and strength stored in the `force` of the triangle
+    pub fn surface_force(&self) -> SurfaceForce {
+        SurfaceForce::from_raw(self.force)
+    }
+
+    /// The verticies of the triangle converted to floating point, with each coordinate multiplied by `scale`
+    pub fn vertices_f32(&self, scale: f32) -> [Point3<f32>; 3] {
+        let convert = |p: Point3<i16>| Point3 {
+            x: p.x as f32 * scale,
+            y: p.y as f32 * scale,
+            z: p.z as f32 * scale,
+        };
+
+        [
+            convert(self.vertices.0),
+            convert(self.vertices.1),
+            convert(self.vertices.2),
+        ]
+    }
+}
+
+/// A level triangle that stores its surface type and terrain as raw sm64 values, for surface types that `Surface` does not list such as those used by modded levels.
+///
+/// `Surface` is a plain `#[repr(u16)]` enum so a `LevelTriangle` has the same layout as libsm64's triangles and can be passed to it without a copy,
+/// this also means it can't hold a value that isn't one of its variants. `RawLevelTriangle` keeps the same layout but gives up the checking and naming of the surface types,
+/// use it with `Sm64::load_level_geometry_raw` and `Sm64::create_dynamic_surface_raw`
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawLevelTriangle {
+    /// The raw sm64 surface type
+    pub kind: u16,
+    pub force: i16,
+    /// The raw sm64 terrain
+    pub terrain: u16,
+    /// The verticies of the triangle, see `LevelTriangle::vertices`
+    pub vertices: (Point3<i16>, Point3<i16>, Point3<i16>),
+}
+
+impl From<LevelTriangle> for RawLevelTriangle {
+    fn from(triangle: LevelTriangle) -> Self {
+        Self {
+            kind: triangle.kind.as_u16(),
+            force: triangle.force,
+            terrain: triangle.terrain.as_u16(),
+            vertices: triangle.vertices,
+        }
+    }
+}
+
+impl TryFrom<RawLevelTriangle> for LevelTriangle {
+    type Error = Error;
+
+    fn try_from(triangle: RawLevelTriangle) -> Result<Self, Self::Error> {
+        Ok(Self {
+            kind: Surface::try_from(triangle.kind)?,
+            force: triangle.force,
+            terrain: Terrain::try_from(triangle.terrain)?,
+            vertices: triangle.vertices,
+        })
+    }
+}
+
+// Every `LevelTriangle` is also a valid `RawLevelTriangle`
+fn as_raw_triangles(geometry: &[LevelTriangle]) -> &[RawLevelTriangle] {
+    unsafe { std::slice::from_raw_parts(geometry.as_ptr() as *const _, geometry.len()) }
+}
+
+/// The direction and strength a surface pushes Mario in, stored in the `force` of a `LevelTriangle`. Used by the `HorizontalWind`, `FlowingWater`, and moving quicksand surfaces
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SurfaceForce {
+    /// The direction of the push in radians, matching the units of `MarioState::face_angle`. Only the top 8 bits of the engine's 16-bit angle are stored
+    pub direction: f32,
+    /// An index into the engine's speed table for the surface, from 0 (fastest) to 3 (slowest). `HorizontalWind` ignores this and derives its speed from Mario's
+    pub strength: u8,
+}
+
+impl SurfaceForce {
+    /// Encode into the value of `LevelTriangle::force`, the strength is placed in the high byte and the direction in the low byte
+    pub fn to_raw(self) -> i16 {
+        let angle = (self.direction / std::f32::consts::TAU * 65536.0).round() as i32;
+        let direction = ((angle >> 8) & 0xff) as u16;
+        let strength = (self.strength.min(3) as u16) << 8;
+
+        (strength | direction) as i16
+    }
+
+    /// Decode from the value of `LevelTriangle::force`
+    pub fn from_raw(force: i16) -> Self {
+        let force = force as u16;
+        let angle = ((force & 0xff) << 8) as i16;
+
+        SurfaceForce {
+            direction: angle as f32 / 65536.0 * std::f32::consts::TAU,
+            strength: (force >> 8) as u8,
+        }
+    }
+}
+
+/// The scale between your world units and sm64 units, used to check that a level can be quantized to the `i16` verticies of the collision geometry without losing too much precision
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LevelScale {
+    units_per_meter: f32,
+}
+
+impl LevelScale {
+    /// Create a scale with the number of sm64 units in one of your world units. Mario is roughly 160 units tall, so a world measured in meters would use about 100
+    pub fn new(units_per_meter: f32) -> Self {
+        Self { units_per_meter }
+    }
+
+    /// The number of sm64 units in one world unit
+    pub fn units_per_meter(&self) -> f32 {
+        self.units_per_meter
+    }
+
+    /// Convert a point in world units to sm64 units, rounding to the nearest unit. Returns `Error::CoordinateOutOfRange` if a coordinate doesn't fit in an `i16`
+    pub fn world_to_sm64(&self, p: Point3<f32>) -> Result<Point3<i16>, Error> {
+        let convert = |v: f32| {
+            let scaled = (v * self.units_per_meter).round();
+
+            if scaled >= i16::MIN as f32 && scaled <= i16::MAX as f32 {
+                Ok(scaled as i16)
+            } else {
+                Err(Error::CoordinateOutOfRange(v))
+            }
+        };
+
+        Ok(Point3 {
+            x: convert(p.x)?,
+            y: convert(p.y)?,
+            z: convert(p.z)?,
+        })
+    }
+
+    /// Convert a point in sm64 units, such as `MarioState::position`, to world units
+    pub fn sm64_to_world(&self, p: Point3<f32>) -> Point3<f32> {
+        Point3 {
+            x: p.x / self.units_per_meter,
+            y: p.y / self.units_per_meter,
+            z: p.z / self.units_per_meter,
+        }
+    }
+
+    /// The largest error in world units introduced by rounding a coordinate to the collision precision
+    pub fn max_error(&self) -> f32 {
+        COLLISION_PRECISION * 0.5 / self.units_per_meter
+    }
+
+    /// The largest distance from the origin in world units that can be represented on each axis
+    pub fn max_extent(&self) -> f32 {
+        i16::MAX as f32 / self.units_per_meter
+    }
+
+    /// Check that coordinates up to `extent` world units from the origin can be represented, and that rounding won't move a vertex by more than `tolerance` world units
+    pub fn validate(&self, extent: f32, tolerance: f32) -> Result<(), Error> {
+        if !(self.units_per_meter.is_finite() && self.units_per_meter > 0.0) {
+            return Err(Error::InvalidScale(format!(
+                "units per meter must be positive, found {}",
+                self.units_per_meter
+            )));
+        }
+
+        if self.max_error() > tolerance {
+            return Err(Error::InvalidScale(format!(
+                "rounding error of {} exceeds tolerance of {}",
+                self.max_error(),
+                tolerance
+            )));
+        }
+
+        if extent > self.max_extent() {
+            return Err(Error::InvalidScale(format!(
+                "extent of {} exceeds the maximum extent of {}",
+                extent,
+                self.max_extent()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// The handedness of a coordinate system, sm64 is right-handed with y up
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Handedness {
+    /// sm64's own coordinate system, nothing is converted
+    #[default]
+    Right,
+    /// A left-handed coordinate system with y up, converted by mirroring across the x-axis
+    Left,
+}
+
+impl Handedness {
+    /// Convert a point or direction between sm64's right-handed space and this handedness, the conversion is its own inverse so it works in both directions
+    pub fn convert(&self, p: Point3<f32>) -> Point3<f32> {
+        match self {
+            Handedness::Right => p,
+            Handedness::Left => Point3 { x: -p.x, ..p },
+        }
+    }
+
+    fn convert_input(&self, input: MarioInput) -> MarioInput {
+        match self {
+            Handedness::Right => input,
+            Handedness::Left => MarioInput {
+                cam_look_x: -input.cam_look_x,
+                stick_x: -input.stick_x,
+                ..input
+            },
+        }
+    }
+
+    fn convert_state(&self, state: MarioState) -> MarioState {
+        match self {
+            Handedness::Right => state,
+            Handedness::Left => MarioState {
+                position: self.convert(state.position),
+                velocity: self.convert(state.velocity),
+                face_angle: -state.face_angle,
+                ground_shadow: state
+                    .ground_shadow
+                    .map(|(position, scale)| (self.convert(position), scale)),
+                wall_normal: state.wall_normal.map(|normal| self.convert(normal)),
+                ..state
+            },
+        }
+    }
+}
+
+/// The input for a frame of Mario's logic
+///
+/// Buttons are read as held or released each tick, a press is registered only on the tick where a button changes from released to held.
+/// The engine has no input buffer, a press that arrives during a tick where Mario can't act on it is dropped. The timing windows the
+/// game does have, such as how soon after landing a double jump can be chained or how soon after hitting a wall a wall kick can be
+/// performed, are fixed in the game logic and can not be adjusted
+///
+/// Holding a button also matters, the height of Mario's jumps depends on how many ticks A stays held after the press. If your input system only reports
+/// presses use a `HeldButton` to hold the button for a number of ticks
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarioInput {
+    ///  The position of the camera on the x-axis, used to adjust the movement of mario based on his postion relative to the camera
+    pub cam_look_x: f32,
+    ///  The position of the camera on the z-axis, used to adjust the movement of mario based on his postion relative to the camera
+    pub cam_look_z: f32,
+    /// The input of the analog control on the x-axis (-1.0..1.0)
+    pub stick_x: f32,
+    /// The input of the analog control on the y-axis (-1.0..1.0)
+    pub stick_y: f32,
+    /// Is the A button pressed
+    pub button_a: bool,
+    /// Is the B button pressed
+    pub button_b: bool,
+    /// Is the Z button pressed
+    pub button_z: bool,
+}
+
+impl MarioInput {
+    /// Set the analog stick and camera so that Mario moves in the direction of `world_dir`, ignoring its y component. The length of `world_dir` is used as the
+    /// stick magnitude up to a maximum of 1.0, pass a shorter vector to walk rather than run.
+    ///
+    /// `camera_yaw` is the direction the camera is looking in radians, using the same convention as `MarioState::face_angle` where 0 looks along the z-axis and
+    /// a quarter turn looks along the x-axis
+    pub fn move_toward(&mut self, world_dir: Point3<f32>, camera_yaw: f32) {
+        self.cam_look_x = camera_yaw.sin();
+        self.cam_look_z = camera_yaw.cos();
+
+        let magnitude = (world_dir.x * world_dir.x + world_dir.z * world_dir.z)
+            .sqrt()
+            .min(1.0);
+
+        if magnitude == 0.0 {
+            self.stick_x = 0.0;
+            self.stick_y = 0.0;
+            return;
+        }
+
+        // libsm64 computes Mario's intended yaw from the stick as atan2(-stick_x, -stick_y) relative to the camera's yaw
+        let relative_yaw = world_dir.x.atan2(world_dir.z) - camera_yaw;
+        self.stick_x = -relative_yaw.sin() * magnitude;
+        self.stick_y = -relative_yaw.cos() * magnitude;
+    }
+}
+
+/// Tracks the held state of a button across ticks for input systems that only report when a button was pressed
+///
+/// ```
+/// # use libsm64::*;
+/// let mut a = HeldButton::new();
+/// let mut input = MarioInput::default();
+///
+/// // A short hop, A is held for 3 ticks
+/// a.press(3);
+/// input.button_a = a.tick();
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct HeldButton {
+    remaining: u32,
+    release: bool,
+}
+
+impl HeldButton {
+    /// Create a released button
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Press the button and hold it for the next `ticks` ticks. If the button is still held from an earlier press it is released for one tick first so the engine sees a new press
+    pub fn press(&mut self, ticks: u32) {
+        self.release = self.remaining > 0;
+        self.remaining = ticks;
+    }
+
+    /// Release the button early
+    pub fn release(&mut self) {
+        self.remaining = 0;
+        self.release = false;
+    }
+
+    /// Is the button still being held
+    pub fn is_held(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Advance by one tick, returning whether the button is held during this tick
+    pub fn tick(&mut self) -> bool {
+        if self.release {
+            self.release = false;
+            return false;
+        }
+
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl From<MarioInput> for libsm64_sys::SM64MarioInputs {
+    fn from(input: MarioInput) -> Self {
+        libsm64_sys::SM64MarioInputs {
+            camLookX: input.cam_look_x,
+            camLookZ: input.cam_look_z,
+            stickX: input.stick_x,
+            stickY: input.stick_y,
+            buttonA: input.button_a as u8,
+            buttonB: input.button_b as u8,
+            buttonZ: input.button_z as u8,
+        }
+    }
+}
+
+/// Mario's state after a tick of logic
+#[derive(Debug, Default, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarioState {
+    /// The position of Mario in 3D space
+    pub position: Point3<f32>,
+    /// The velocity of Mario on each axis
+    pub velocity: Point3<f32>,
+    /// The direction Mario is facing
+    pub face_angle: f32,
+    /// Mario's current health
+    pub health: i16,
+    /// The raw value of Mario's current action, see `Action` for the known values
+    pub action: u32,
+    /// Mario's flags, such as which cap he is wearing
+    pub flags: MarioFlags,
+    /// The number of ticks Mario will remain invincible for, 0 when he is not invincible
+    pub invincibility_timer: i16,
+    /// The point on the floor beneath Mario and the scale of his blob shadow, the shadow shrinks to half size as Mario rises 600 units above the floor.
+    /// `None` if there is no floor beneath Mario
+    pub ground_shadow: Option<(Point3<f32>, f32)>,
+    /// The normal of the wall Mario is pressed against, `None` if he is not touching a wall or `Mario::set_wall_detection` is not enabled
+    pub wall_normal: Option<Point3<f32>>,
+    /// Mario's remaining lives
+    #[cfg(feature = "counters")]
+    pub num_lives: i16,
+    /// The number of coins Mario has collected
+    #[cfg(feature = "counters")]
+    pub num_coins: u16,
+    /// The number of stars Mario has collected
+    #[cfg(feature = "counters")]
+    pub num_stars: u16,
+}
+
+impl From<libsm64_sys::SM64MarioState> for MarioState {
+    fn from(state: libsm64_sys::SM64MarioState) -> Self {
+        let position = Point3 {
+            x: state.position[0],
+            y: state.position[1],
+            z: state.position[2],
+        };
+        let velocity = Point3 {
+            x: state.velocity[0],
+            y: state.velocity[1],
+            z: state.velocity[2],
+        };
+        MarioState {
+            position,
+            velocity,
+            face_angle: state.faceAngle,
+            health: state.health,
+            action: state.action,
+            flags: MarioFlags::from_bits_retain(state.flags),
+            invincibility_timer: state.invincTimer,
+            ground_shadow: None,
+            wall_normal: None,
+            #[cfg(feature = "counters")]
+            num_lives: state.numLives,
+            #[cfg(feature = "counters")]
+            num_coins: state.numCoins as u16,
+            #[cfg(feature = "counters")]
+            num_stars: state.numStars as u16,
+        }
+    }
+}
+
+impl MarioState {
+    /// The group of Mario's current action, useful for reacting to broad changes such as Mario entering water or a cutscene
+    pub fn action_group(&self) -> ActionGroup {
+        match self.action & ACT_GROUP_MASK {
+            0x000 => ActionGroup::Stationary,
+            0x040 => ActionGroup::Moving,
+            0x080 => ActionGroup::Airborne,
+            0x0C0 => ActionGroup::Submerged,
+            0x100 => ActionGroup::Cutscene,
+            0x140 => ActionGroup::Automatic,
+            0x180 => ActionGroup::Object,
+            _ => ActionGroup::Unknown,
+        }
+    }
+
+    /// A coarse summary of what Mario is doing, derived from `action_group`
+    pub fn activity_level(&self) -> ActivityLevel {
+        match self.action_group() {
+            ActionGroup::Stationary => ActivityLevel::Idle,
+            ActionGroup::Moving | ActionGroup::Submerged => ActivityLevel::Walking,
+            ActionGroup::Airborne => ActivityLevel::Airborne,
+            ActionGroup::Cutscene
+            | ActionGroup::Automatic
+            | ActionGroup::Object
+            | ActionGroup::Unknown => ActivityLevel::Interacting,
+        }
+    }
+
+    /// Returns true if Mario is in a stationary action, such as standing, sleeping, or crouching
+    pub fn is_idle(&self) -> bool {
+        self.activity_level() == ActivityLevel::Idle
+    }
+
+    /// Returns true if Mario has run out of health, as in the original game this is when his health meter has no wedges left (health below `0x100`)
+    pub fn is_dead(&self) -> bool {
+        self.health < 0x100
+    }
+
+    /// The caps Mario is wearing, read from his `flags`. Power-up caps are worn together with the normal cap, so Mario wearing the wing cap reports both `NORMAL` and `WING`
+    pub fn caps(&self) -> CapFlags {
+        CapFlags::from_bits_truncate(self.flags.bits())
+    }
+
+    /// How far Mario is through grabbing and climbing up a ledge in the range 0.0 to 1.0, 0.0 while hanging and increasing with each stage of the climb.
+    /// Returns `None` when Mario is not on a ledge, including once he has finished climbing and is back on his feet.
+    ///
+    /// libsm64 doesn't report the engine's action timer, so the progress is derived from which stage of the climb Mario's action is in rather than advancing every tick.
+    /// Climbing down from the top of a ledge ends with Mario hanging from it, so it is reported as 0.0
+    pub fn ledge_grab(&self) -> Option<f32> {
+        match Action::from_raw(self.action)? {
+            Action::LedgeGrab | Action::LedgeClimbDown => Some(0.0),
+            Action::LedgeClimbSlow1 | Action::LedgeClimbFast => Some(0.5),
+            Action::LedgeClimbSlow2 => Some(0.75),
+            _ => None,
+        }
+    }
+}
+
+/// Mario's geometry
+#[derive(Clone)]
+pub struct MarioGeometry {
+    position: Vec<Point3<f32>>,
+    normal: Vec<Point3<f32>>,
+    color: Vec<Color>,
+    uv: Vec<Point2<f32>>,
+    num_triangles: usize,
+    checksum: OnceCell<u64>,
+    prev_checksum: Option<u64>,
+    prev_triangles: usize,
+    interleaved: Vec<MarioVertexPacked>,
+    interleaved_dirty: bool,
+}
+
+impl MarioGeometry {
+    fn new() -> Self {
+        Self {
+            position: vec![Point3::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
+            normal: vec![Point3::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
+            color: vec![Color::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
+            uv: vec![Point2::default(); libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3],
+            num_triangles: 0,
+            checksum: OnceCell::new(),
+            prev_checksum: None,
+            prev_triangles: 0,
+            interleaved: vec![
+                MarioVertexPacked::default();
+                libsm64_sys::SM64_GEO_MAX_TRIANGLES as usize * 3
+            ],
+            interleaved_dirty: true,
+        }
+    }
+
+    fn update(&mut self, num_triangles: usize) {
+        // The checksum is only computed when `is_dirty` asks for it, so the previous tick's is only known if it was asked for then
+        self.prev_checksum = self.checksum.take();
+        self.prev_triangles = self.num_triangles;
+        self.num_triangles = num_triangles;
+        self.interleaved_dirty = true;
+    }
+
+    // Mirror the geometry across the x-axis, swapping the winding so the front faces still face out
+    fn mirror(&mut self, num_triangles: usize) {
+        for i in 0..num_triangles * 3 {
+            self.position[i].x = -self.position[i].x;
+            self.normal[i].x = -self.normal[i].x;
+        }
+
+        for tri in 0..num_triangles {
+            let (b, c) = (tri * 3 + 1, tri * 3 + 2);
+            self.position.swap(b, c);
+            self.normal.swap(b, c);
+            self.color.swap(b, c);
+            self.uv.swap(b, c);
+        }
+    }
+
+    // FNV-1a over the bits of each float in use
+    fn compute_checksum(&self) -> u64 {
+        let positions = self.positions().iter().flat_map(|p| [p.x, p.y, p.z]);
+        let normals = self.normals().iter().flat_map(|n| [n.x, n.y, n.z]);
+        let colors = self.colors().iter().flat_map(|c| [c.r, c.g, c.b]);
+        let uvs = self.uvs().iter().flat_map(|uv| [uv.x, uv.y]);
+
+        positions
+            .chain(normals)
+            .chain(colors)
+            .chain(uvs)
+            .fold(0xcbf29ce484222325, |hash, value| {
+                (hash ^ value.to_bits() as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+
+    /// Returns true if the geometry changed during the most recent tick, when false the geometry is identical to the previous tick and does not need to be uploaded again
+    ///
+    /// The check hashes the geometry the first time it is called after each `tick`. If it wasn't called during the previous tick there is nothing to compare against and the geometry is reported as dirty
+    pub fn is_dirty(&self) -> bool {
+        let checksum = *self.checksum.get_or_init(|| self.compute_checksum());
+        self.prev_triangles != self.num_triangles || self.prev_checksum != Some(checksum)
+    }
+
+    /// The geometry represented as a series of vertices, every 3 verticies is a new triangle. Includes position, normal, color, and texture coordinates
+    pub fn vertices(&self) -> impl Iterator<Item = MarioVertex> + '_ {
+        let positions = self.position.iter().copied();
+        let normals = self.normal.iter().copied();
+        let color = self.color.iter().copied();
+        let uv = self.uv.iter().copied();
+
+        positions
+            .zip(normals)
+            .zip(color)
+            .zip(uv)
+            .take(self.num_triangles * 3)
+            .map(|(((position, normal), color), uv)| MarioVertex {
+                position,
+                normal,
+                color,
+                uv,
+            })
+    }
+
+    /// The geometry represented as a series of triangles. Includes position, normal, color, and texture coordinates
+    pub fn triangles(&self) -> impl Iterator<Item = (MarioVertex, MarioVertex, MarioVertex)> + '_ {
+        let positions = self.position.chunks_exact(3);
+        let normals = self.normal.chunks_exact(3);
+        let color = self.color.chunks_exact(3);
+        let uv = self.uv.chunks_exact(3);
+
+        positions
+            .zip(normals)
+            .zip(color)
+            .zip(uv)
+            .take(self.num_triangles)
+            .map(|(((positions, normals), colors), uvs)| {
+                let a = MarioVertex {
+                    position: positions[0],
+                    normal: normals[0],
+                    color: colors[0],
+                    uv: uvs[0],
+                };
+                let b = MarioVertex {
+                    position: positions[1],
+                    normal: normals[1],
+                    color: colors[1],
+                    uv: uvs[1],
+                };
+                let c = MarioVertex {
+                    position: positions[2],
+                    normal: normals[2],
+                    color: colors[2],
+                    uv: uvs[2],
+                };
+                (a, b, c)
+            })
+    }
+
+    /// The position elements of Mario's verticies
+    pub fn positions(&self) -> &[Point3<f32>] {
+        &self.position[0..self.num_triangles * 3]
+    }
+
+    /// The normal elements of Mario's verticies
+    pub fn normals(&self) -> &[Point3<f32>] {
+        &self.normal[0..self.num_triangles * 3]
+    }
+
+    /// The color elements of Mario's verticies
+    pub fn colors(&self) -> &[Color] {
+        &self.color[0..self.num_triangles * 3]
+    }
+
+    /// The texture coordinate elements of Mario's verticies
+    pub fn uvs(&self) -> &[Point2<f32>] {
+        &self.uv[0..self.num_triangles * 3]
+    }
+
+    /// The position, normal, color, and texture coordinate slices bundled together, suitable for uploading directly to the GPU
+    pub fn view(&self) -> GeometryView<'_> {
+        GeometryView {
+            positions: self.positions(),
+            normals: self.normals(),
+            colors: self.colors(),
+            uvs: self.uvs(),
+            num_triangles: self.num_triangles,
+            num_vertices: self.num_triangles * 3,
+        }
+    }
+
+    /// The verticies in use interleaved into a single buffer, ready to be uploaded to the GPU. See `MarioVertexPacked` for the byte layout.
+    /// The buffer is allocated once for the largest possible model and refilled in place on the first call after each `tick`, so Marios whose interleaved geometry is never used don't pay for it
+    pub fn interleaved(&mut self) -> &[MarioVertexPacked] {
+        let num_vertices = self.num_triangles * 3;
+
+        if self.interleaved_dirty {
+            if self.interleaved.len() < num_vertices {
+                self.interleaved
+                    .resize(num_vertices, MarioVertexPacked::default());
+            }
+
+            for ((((packed, position), normal), color), uv) in self.interleaved[..num_vertices]
+                .iter_mut()
+                .zip(&self.position)
+                .zip(&self.normal)
+                .zip(&self.color)
+                .zip(&self.uv)
+            {
+                *packed = MarioVertex {
+                    position: *position,
+                    normal: *normal,
+                    color: *color,
+                    uv: *uv,
+                }
+                .into();
+            }
+            self.interleaved_dirty = false;
+        }
+
+        &self.interleaved[..num_vertices]
+    }
+
+    /// A tangent for each vertex in use for normal mapping, as xyz plus the handedness of the bitangent in w. Tangents are computed from the uvs of each triangle and averaged across
+    /// vertices that share a position and normal. Triangles without usable uvs, such as those that are untextured, are given an arbitrary tangent perpendicular to the normal
+    pub fn tangents(&self) -> Vec<[f32; 4]> {
+        fn sub(a: Point3<f32>, b: Point3<f32>) -> [f32; 3] {
+            [a.x - b.x, a.y - b.y, a.z - b.z]
+        }
+        fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+            a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+        }
+        fn normalize(a: [f32; 3]) -> Option<[f32; 3]> {
+            let len = dot(a, a).sqrt();
+            (len > 1e-6).then(|| [a[0] / len, a[1] / len, a[2] / len])
+        }
+        fn reject(a: [f32; 3], n: [f32; 3]) -> [f32; 3] {
+            let d = dot(a, n);
+            [a[0] - n[0] * d, a[1] - n[1] * d, a[2] - n[2] * d]
+        }
+
+        let key = |i: usize| {
+            let (p, n) = (self.position[i], self.normal[i]);
+            [p.x, p.y, p.z, n.x, n.y, n.z].map(f32::to_bits)
+        };
+
+        let mut sums: HashMap<[u32; 6], ([f32; 3], [f32; 3])> = HashMap::new();
+        for tri in 0..self.num_triangles {
+            let [a, b, c] = [tri * 3, tri * 3 + 1, tri * 3 + 2];
+            let (e1, e2) = (
+                sub(self.position[b], self.position[a]),
+                sub(self.position[c], self.position[a]),
+            );
+            let (du1, dv1) = (self.uv[b].x - self.uv[a].x, self.uv[b].y - self.uv[a].y);
+            let (du2, dv2) = (self.uv[c].x - self.uv[a].x, self.uv[c].y - self.uv[a].y);
+
+            let r = du1 * dv2 - du2 * dv1;
+            if r.abs() < 1e-12 {
+                continue;
+            }
+
+            let t = [0, 1, 2].map(|i| (e1[i] * dv2 - e2[i] * dv1) / r);
+            let bt = [0, 1, 2].map(|i| (e2[i] * du1 - e1[i] * du2) / r);
+
+            for v in [a, b, c] {
+                let sum = sums.entry(key(v)).or_insert(([0.0; 3], [0.0; 3]));
+                for i in 0..3 {
+                    sum.0[i] += t[i];
+                    sum.1[i] += bt[i];
+                }
+            }
+        }
+
+        (0..self.num_triangles * 3)
+            .map(|v| {
+                let n = self.normal[v];
+                let n = [n.x, n.y, n.z];
+                let (t, bt) = sums.get(&key(v)).copied().unwrap_or_default();
+
+                let tangent = normalize(reject(t, n)).unwrap_or_else(|| {
+                    let axis = if n[0].abs() < 0.9 {
+                        [1.0, 0.0, 0.0]
+                    } else {
+                        [0.0, 1.0, 0.0]
+                    };
+                    normalize(reject(axis, n)).unwrap_or(axis)
+                });
+
+                let cross = [
+                    n[1] * tangent[2] - n[2] * tangent[1],
+                    n[2] * tangent[0] - n[0] * tangent[2],
+                    n[0] * tangent[1] - n[1] * tangent[0],
+                ];
+                let w = if dot(cross, bt) < 0.0 { -1.0 } else { 1.0 };
+
+                [tangent[0], tangent[1], tangent[2], w]
+            })
+            .collect()
+    }
+
+    /// The geometry as a Wavefront OBJ, with vertex colors following each position. Texture coordinates are flipped vertically as OBJ places the origin in the bottom left
+    pub fn to_obj(&self) -> String {
+        self.write_obj(None)
+    }
+
+    /// The geometry as a Wavefront OBJ that uses `material` from the companion `mtl_file`
+    pub fn to_obj_with_mtl(&self, mtl_file: &str, material: &str) -> String {
+        self.write_obj(Some((mtl_file, material)))
+    }
+
+    fn write_obj(&self, mtl: Option<(&str, &str)>) -> String {
+        use std::fmt::Write;
+
+        let mut obj = String::new();
+
+        if let Some((mtl_file, _)) = mtl {
+            writeln!(obj, "mtllib {}", mtl_file).unwrap();
+        }
+        for (p, c) in self.positions().iter().zip(self.colors()) {
+            writeln!(obj, "v {} {} {} {} {} {}", p.x, p.y, p.z, c.r, c.g, c.b).unwrap();
+        }
+        for n in self.normals() {
+            writeln!(obj, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+        }
+        for uv in self.uvs() {
+            writeln!(obj, "vt {} {}", uv.x, 1.0 - uv.y).unwrap();
+        }
+        if let Some((_, material)) = mtl {
+            writeln!(obj, "usemtl {}", material).unwrap();
+        }
+        for tri in 0..self.num_triangles {
+            let (a, b, c) = (tri * 3 + 1, tri * 3 + 2, tri * 3 + 3);
+            writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}").unwrap();
+        }
+
+        obj
+    }
+
+    /// The geometry as a self-contained binary glTF (.glb) with `texture` embedded as a PNG. The mesh has POSITION, NORMAL, TEXCOORD_0, and COLOR_0 attributes and one
+    /// material using the texture as its base color.
+    ///
+    /// **Note:** glTF viewers multiply the texture by the vertex color, while libsm64 expects the texture to be blended over the vertex color by its alpha
+    #[cfg(feature = "gltf")]
+    pub fn to_gltf(&self, texture: &Texture) -> Vec<u8> {
+        // `is_multiple_of` needs Rust 1.87
+        #[allow(clippy::manual_is_multiple_of)]
+        fn pad(buffer: &mut Vec<u8>, byte: u8) {
+            while buffer.len() % 4 != 0 {
+                buffer.push(byte);
+            }
+        }
+
+        let mut png = Vec::new();
+        texture
+            .to_image()
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .expect("Encoding a PNG in memory can not fail");
+
+        let count = self.num_triangles * 3;
+        let mut bin = Vec::new();
+        let mut views = Vec::new();
+        let mut push_view = |bin: &mut Vec<u8>, floats: &mut dyn Iterator<Item = f32>| {
+            let offset = bin.len();
+            bin.extend(floats.flat_map(f32::to_le_bytes));
+            views.push((offset, bin.len() - offset));
+        };
+
+        push_view(
+            &mut bin,
+            &mut self.positions().iter().flat_map(|p| [p.x, p.y, p.z]),
+        );
+        push_view(
+            &mut bin,
+            &mut self.normals().iter().flat_map(|n| [n.x, n.y, n.z]),
+        );
+        push_view(&mut bin, &mut self.uvs().iter().flat_map(|uv| [uv.x, uv.y]));
+        push_view(
+            &mut bin,
+            &mut self.colors().iter().flat_map(|c| [c.r, c.g, c.b]),
+        );
+        views.push((bin.len(), png.len()));
+        bin.extend(&png);
+        pad(&mut bin, 0);
+
+        let (min, max) =
+            self.positions()
+                .iter()
+                .fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), p| {
+                    (
+                        [min[0].min(p.x), min[1].min(p.y), min[2].min(p.z)],
+                        [max[0].max(p.x), max[1].max(p.y), max[2].max(p.z)],
+                    )
+                });
+
+        let buffer_views = views
+            .iter()
+            .map(|(offset, length)| {
+                format!(
+                    r#"{{"buffer":0,"byteOffset":{},"byteLength":{}}}"#,
+                    offset, length
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        // glTF requires at least one element in every accessor, so an empty frame has no mesh
+        let (nodes, meshes) = if count > 0 {
+            (
+                r#""nodes":[{"mesh":0}],"scenes":[{"nodes":[0]}],"#.to_string(),
+                format!(
+                    concat!(
+                        r#""meshes":[{{"primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1,"TEXCOORD_0":2,"COLOR_0":3}},"material":0}}]}}],"#,
+                        r#""accessors":["#,
+                        r#"{{"bufferView":0,"componentType":5126,"count":{count},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}},"#,
+                        r#"{{"bufferView":1,"componentType":5126,"count":{count},"type":"VEC3"}},"#,
+                        r#"{{"bufferView":2,"componentType":5126,"count":{count},"type":"VEC2"}},"#,
+                        r#"{{"bufferView":3,"componentType":5126,"count":{count},"type":"VEC3"}}"#,
+                        r#"],"#
+                    ),
+                    min[0],
+                    min[1],
+                    min[2],
+                    max[0],
+                    max[1],
+                    max[2],
+                    count = count
+                ),
+            )
+        } else {
+            (r#""scenes":[{"nodes":[]}],"#.to_string(), String::new())
+        };
+
+        let mut json = format!(
+            concat!(
+                r#"{{"asset":{{"version":"2.0","generator":"libsm64-rust"}},"scene":0,{}{}"#,
+                r#""materials":[{{"pbrMetallicRoughness":{{"baseColorTexture":{{"index":0}},"metallicFactor":0.0}}}}],"#,
+                r#""textures":[{{"source":0,"sampler":0}}],"#,
+                r#""samplers":[{{"magFilter":9728,"minFilter":9728}}],"#,
+                r#""images":[{{"bufferView":4,"mimeType":"image/png"}}],"#,
+                r#""bufferViews":[{}],"buffers":[{{"byteLength":{}}}]}}"#
+            ),
+            nodes,
+            meshes,
+            buffer_views,
+            bin.len()
+        )
+        .into_bytes();
+        pad(&mut json, b' ');
+
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+        let mut glb = Vec::with_capacity(length);
+        glb.extend(b"glTF");
+        glb.extend(2u32.to_le_bytes());
+        glb.extend((length as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(json);
+        glb.extend((bin.len() as u32).to_le_bytes());
+        glb.extend(b"BIN\0");
+        glb.extend(bin);
+
+        glb
+    }
+
+    /// The geometry as an indexed mesh, vertices with exactly the same position, normal, color, and uv are merged into one
+    pub fn indexed(&self) -> (Vec<MarioVertex>, Vec<u32>) {
+        self.indexed_with_epsilon(0.0)
+    }
+
+    /// The geometry as an indexed mesh, vertices are merged when each of their components round to the same multiple of `epsilon`
+    pub fn indexed_with_epsilon(&self, epsilon: f32) -> (Vec<MarioVertex>, Vec<u32>) {
+        let key = |v: &MarioVertex| -> [u32; 11] {
+            let components = [
+                v.position.x,
+                v.position.y,
+                v.position.z,
+                v.normal.x,
+                v.normal.y,
+                v.normal.z,
+                v.color.r,
+                v.color.g,
+                v.color.b,
+                v.uv.x,
+                v.uv.y,
+            ];
+
+            components.map(|c| {
+                if epsilon > 0.0 {
+                    ((c / epsilon).round() as i32) as u32
+                } else {
+                    c.to_bits()
+                }
+            })
+        };
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(self.num_triangles * 3);
+        let mut lookup = HashMap::new();
+
+        for vertex in self.vertices() {
+            let index = *lookup.entry(key(&vertex)).or_insert_with(|| {
+                vertices.push(vertex);
+                vertices.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+
+        (vertices, indices)
+    }
+
+    /// Deep copy only the verticies in use as of the current tick. Like `clone` the copy is independent of the `Mario` it came from, so it can be kept across ticks or sent to another thread, but it skips the unused tail of the buffers
+    pub fn snapshot(&self) -> MarioGeometry {
+        MarioGeometry {
+            position: self.positions().to_vec(),
+            normal: self.normals().to_vec(),
+            color: self.colors().to_vec(),
+            uv: self.uvs().to_vec(),
+            num_triangles: self.num_triangles,
+            checksum: self.checksum.clone(),
+            prev_checksum: self.prev_checksum,
+            prev_triangles: self.prev_triangles,
+            interleaved: Vec::new(),
+            interleaved_dirty: true,
+        }
+    }
+
+    /// A copy of the geometry with `m` applied to each vertex, `m` is row-major and is applied to column vectors so the translation is in the last column.
+    /// Normals are transformed by the inverse-transpose of `m`, and if `m` mirrors the geometry the winding of each triangle is reversed to keep the front faces facing out
+    pub fn transformed(&self, m: &[[f32; 4]; 4]) -> MarioGeometry {
+        let mut geometry = self.snapshot();
+
+        for p in geometry.position.iter_mut() {
+            *p = Point3 {
+                x: m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
+                y: m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
+                z: m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3],
+            };
+        }
+
+        // The cofactor matrix is the inverse-transpose scaled by the determinant, the scale
+        // is removed when normalizing but its sign must be kept
+        let cofactor = [
+            [
+                m[1][1] * m[2][2] - m[1][2] * m[2][1],
+                m[1][2] * m[2][0] - m[1][0] * m[2][2],
+                m[1][0] * m[2][1] - m[1][1] * m[2][0],
+            ],
+            [
+                m[0][2] * m[2][1] - m[0][1] * m[2][2],
+                m[0][0] * m[2][2] - m[0][2] * m[2][0],
+                m[0][1] * m[2][0] - m[0][0] * m[2][1],
+            ],
+            [
+                m[0][1] * m[1][2] - m[0][2] * m[1][1],
+                m[0][2] * m[1][0] - m[0][0] * m[1][2],
+                m[0][0] * m[1][1] - m[0][1] * m[1][0],
+            ],
+        ];
+        let det = m[0][0] * cofactor[0][0] + m[0][1] * cofactor[0][1] + m[0][2] * cofactor[0][2];
+        let sign = if det < 0.0 { -1.0 } else { 1.0 };
+
+        for n in geometry.normal.iter_mut() {
+            let x = sign * (cofactor[0][0] * n.x + cofactor[0][1] * n.y + cofactor[0][2] * n.z);
+            let y = sign * (cofactor[1][0] * n.x + cofactor[1][1] * n.y + cofactor[1][2] * n.z);
+            let z = sign * (cofactor[2][0] * n.x + cofactor[2][1] * n.y + cofactor[2][2] * n.z);
+            let len = (x * x + y * y + z * z).sqrt();
+
+            if len > 0.0 {
+                *n = Point3 {
+                    x: x / len,
+                    y: y / len,
+                    z: z / len,
+                };
+            }
+        }
+
+        if det < 0.0 {
+            for tri in 0..geometry.num_triangles {
+                let (b, c) = (tri * 3 + 1, tri * 3 + 2);
+                geometry.position.swap(b, c);
+                geometry.normal.swap(b, c);
+                geometry.color.swap(b, c);
+                geometry.uv.swap(b, c);
+            }
+        }
+        geometry.interleaved_dirty = true;
+        geometry.checksum.take();
+
+        geometry
+    }
+
+    /// Fill `verts` and `indices` with a flat copy of the geometry for simple debug renderers, any existing contents are cleared so the buffers can be reused each frame.
+    /// Each vertex is 8 floats laid out as position xyz, normal xyz, then uv, and each triangle is 3 indices into those vertices
+    pub fn fill_debug_mesh(&self, verts: &mut Vec<f32>, indices: &mut Vec<u32>) {
+        verts.clear();
+        indices.clear();
+
+        for ((p, n), uv) in self.positions().iter().zip(self.normals()).zip(self.uvs()) {
+            verts.extend_from_slice(&[p.x, p.y, p.z, n.x, n.y, n.z, uv.x, uv.y]);
+        }
+
+        indices.extend(0..self.positions().len() as u32);
+    }
+
+    /// A copy of the geometry scaled by `factor` around `center`, useful for rendering a larger or smaller Mario.
+    /// This only affects the rendered geometry, the engine has no concept of Mario's scale and his collision is unchanged
+    pub fn scaled_about(&self, center: Point3<f32>, factor: f32) -> MarioGeometry {
+        let offset = 1.0 - factor;
+
+        self.transformed(&[
+            [factor, 0.0, 0.0, center.x * offset],
+            [0.0, factor, 0.0, center.y * offset],
+            [0.0, 0.0, factor, center.z * offset],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// The normal of each triangle, oriented to face the same side as the triangle's vertex normals. Degenerate triangles have a zero normal
+    pub fn face_normals(&self) -> Vec<Point3<f32>> {
+        self.positions()
+            .chunks_exact(3)
+            .zip(self.normals().chunks_exact(3))
+            .map(|(p, n)| {
+                let ab = [p[1].x - p[0].x, p[1].y - p[0].y, p[1].z - p[0].z];
+                let ac = [p[2].x - p[0].x, p[2].y - p[0].y, p[2].z - p[0].z];
+                let mut x = ab[1] * ac[2] - ab[2] * ac[1];
+                let mut y = ab[2] * ac[0] - ab[0] * ac[2];
+                let mut z = ab[0] * ac[1] - ab[1] * ac[0];
+
+                let vertex_x = n[0].x + n[1].x + n[2].x;
+                let vertex_y = n[0].y + n[1].y + n[2].y;
+                let vertex_z = n[0].z + n[1].z + n[2].z;
+                if x * vertex_x + y * vertex_y + z * vertex_z < 0.0 {
+                    x = -x;
+                    y = -y;
+                    z = -z;
+                }
+
+                let len = (x * x + y * y + z * z).sqrt();
+                if len > 0.0 {
+                    Point3 {
+                        x: x / len,
+                        y: y / len,
+                        z: z / len,
+                    }
+                } else {
+                    Point3::default()
+                }
+            })
+            .collect()
+    }
+
+    /// A copy of the geometry with each vertex normal replaced by the normal of its triangle, for flat shading
+    pub fn with_flat_normals(&self) -> MarioGeometry {
+        let mut geometry = self.snapshot();
+
+        for (normals, face_normal) in geometry.normal.chunks_exact_mut(3).zip(self.face_normals()) {
+            normals.fill(face_normal);
+        }
+        geometry.interleaved_dirty = true;
+        geometry.checksum.take();
+
+        geometry
+    }
+
+    /// The ranges of verticies that differ from `prev`, allowing only the changed portion of a vertex buffer to be updated
+    pub fn diff(&self, prev: &MarioGeometry) -> ChangedRanges {
+        if self.num_triangles != prev.num_triangles {
+            return ChangedRanges::All;
+        }
+
+        let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+
+        for (idx, (vertex, prev_vertex)) in self.vertices().zip(prev.vertices()).enumerate() {
+            if vertex == prev_vertex {
+                continue;
+            }
+
+            match ranges.last_mut() {
+                Some(range) if range.end == idx => range.end += 1,
+                _ => ranges.push(idx..idx + 1),
+            }
+        }
+
+        ChangedRanges::Ranges(ranges)
+    }
+}
+
+/// A view of Mario's geometry as of the current tick, every slice is exactly `num_vertices` long and every 3 verticies is a new triangle.
+///
+/// Each slice is tightly packed, `Point3<f32>` and `Color` are three `f32` (12 bytes), and `Point2<f32>` is two `f32` (8 bytes)
+#[derive(Copy, Clone, Debug)]
+pub struct GeometryView<'a> {
+    /// The position of each vertex
+    pub positions: &'a [Point3<f32>],
+    /// The normal of each vertex
+    pub normals: &'a [Point3<f32>],
+    /// The color of each vertex
+    pub colors: &'a [Color],
+    /// The texture coordinate of each vertex
+    pub uvs: &'a [Point2<f32>],
+    /// The number of triangles
+    pub num_triangles: usize,
+    /// The number of verticies, always `num_triangles * 3`
+    pub num_vertices: usize,
+}
+
+/// The verticies that changed between two versions of Mario's geometry
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangedRanges {
+    /// The number of triangles differs, all verticies should be considered changed
+    All,
+    /// The ranges of vertex indices that changed, empty when nothing changed
+    Ranges(Vec<std::ops::Range<usize>>),
+}
+
+impl ChangedRanges {
+    /// Returns true if no verticies changed
+    pub fn is_empty(&self) -> bool {
+        match self {
+            ChangedRanges::All => false,
+            ChangedRanges::Ranges(ranges) => ranges.is_empty(),
+        }
+    }
+}
+
+impl<'a> From<&'a mut MarioGeometry> for libsm64_sys::SM64MarioGeometryBuffers {
+    fn from(geo: &'a mut MarioGeometry) -> libsm64_sys::SM64MarioGeometryBuffers {
+        libsm64_sys::SM64MarioGeometryBuffers {
+            position: geo.position.as_mut_ptr() as *mut _,
+            normal: geo.normal.as_mut_ptr() as *mut _,
+            color: geo.color.as_mut_ptr() as *mut _,
+            uv: geo.uv.as_mut_ptr() as *mut _,
+            numTrianglesUsed: geo.position.len() as u16 / 3,
+        }
+    }
+}
+
+/// A vertex that makes up Mario's model, with the `bytemuck` feature it is `Pod` and can be cast directly into a vertex buffer.
+/// It has the same layout as `MarioVertexPacked`
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MarioVertex {
+    /// The position of the vertex
+    pub position: Point3<f32>,
+    /// The normal of the vertex
+    pub normal: Point3<f32>,
+    /// The color of the vertex
+    pub color: Color,
+    /// The texture coordinate of the vertex
+    pub uv: Point2<f32>,
+}
+
+/// A vertex of `MarioGeometry::interleaved` made only of plain `f32` arrays, for shader authors and graphics APIs that don't know about `Point3`.
+/// With the `bytemuck` feature it is `Pod`
+///
+/// Every field is made of `f32`s with no padding, giving a 44 byte stride:
+///
+/// | Offset | Field      | Format      |
+/// |--------|------------|-------------|
+/// | 0      | `position` | `f32` x 3   |
+/// | 12     | `normal`   | `f32` x 3   |
+/// | 24     | `color`    | `f32` x 3   |
+/// | 36     | `uv`       | `f32` x 2   |
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct MarioVertexPacked {
+    /// The position of the vertex
+    pub position: [f32; 3],
+    /// The normal of the vertex
+    pub normal: [f32; 3],
+    /// The color of the vertex as rgb
+    pub color: [f32; 3],
+    /// The texture coordinate of the vertex
+    pub uv: [f32; 2],
+}
+
+impl From<MarioVertex> for MarioVertexPacked {
+    fn from(v: MarioVertex) -> Self {
+        MarioVertexPacked {
+            position: [v.position.x, v.position.y, v.position.z],
+            normal: [v.normal.x, v.normal.y, v.normal.z],
+            color: [v.color.r, v.color.g, v.color.b],
+            uv: [v.uv.x, v.uv.y],
+        }
+    }
+}
+
+/// The surface terrain of a triangle
+#[repr(u16)]
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Terrain {
+    Grass = 0x0000,
+    Stone = 0x0001,
+    Snow = 0x0002,
+    Sand = 0x0003,
+    Spooky = 0x0004,
+    Water = 0x0005,
+    Slide = 0x0006,
+    Mask = 0x0007,
+}
+
+impl Terrain {
+    /// The raw sm64 value of the terrain
+    pub fn as_u16(&self) -> u16 {
+        *self as u16
+    }
+
+    fn from_raw(value: u16) -> Option<Self> {
+        let terrain = match value {
+            0x0000 => Terrain::Grass,
+            0x0001 => Terrain::Stone,
+            0x0002 => Terrain::Snow,
+            0x0003 => Terrain::Sand,
+            0x0004 => Terrain::Spooky,
+            0x0005 => Terrain::Water,
+            0x0006 => Terrain::Slide,
+            0x0007 => Terrain::Mask,
+            _ => return None,
+        };
+
+        Some(terrain)
+    }
+}
+
+impl TryFrom<u16> for Terrain {
+    type Error = Error;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Terrain::from_raw(value).ok_or(Error::UnknownTerrain(value))
+    }
+}
+
+/// The surface type of a triangle
+///
+/// New surface types may be added, surface types that are not listed, such as those used by modded levels, can be loaded with `RawLevelTriangle`
+#[repr(u16)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Surface {
+    Default = 0x0000,
+    Burning = 0x0001,
+    _0004 = 0x0004,
+    Hangable = 0x0005,
+    Slow = 0x0009,
+    DeathPlane = 0x000A,
+    CloseCamera = 0x000B,
+    Water = 0x000D,
+    FlowingWater = 0x000E,
+    Intangible = 0x0012,
+    VerySlippery = 0x0013,
+    Slippery = 0x0014,
+    NotSlippery = 0x0015,
+    TtmVines = 0x0016,
+    MgrMusic = 0x001A,
+    InstantWarp1b = 0x001B,
+    InstantWarp1c = 0x001C,
+    InstantWarp1d = 0x001D,
+    InstantWarp1e = 0x001E,
+    ShallowQuicksand = 0x0021,
+    DeepQuicksand = 0x0022,
+    InstantQuicksand = 0x0023,
+    DeepMovingQuicksand = 0x0024,
+    ShallowMovingQuicksand = 0x0025,
+    Quicksand = 0x0026,
+    MovingQuicksand = 0x0027,
+    WallMisc = 0x0028,
+    NoiseDefault = 0x0029,
+    NoiseSlippery = 0x002A,
+    HorizontalWind = 0x002C,
+    InstantMovingQuicksand = 0x002D,
+    Ice = 0x002E,
+    LookUpWarp = 0x002F,
+    Hard = 0x0030,
+    Warp = 0x0032,
+    TimerStart = 0x0033,
+    TimerEnd = 0x0034,
+    HardSlippery = 0x0035,
+    HardVerySlippery = 0x0036,
+    HardNotSlippery = 0x0037,
+    VerticalWind = 0x0038,
+    BossFightCamera = 0x0065,
+    CameraFreeRoam = 0x0066,
+    Thi3Wallkick = 0x0068,
+    CameraPlatform = 0x0069,
+    CameraMiddle = 0x006E,
+    CameraRotateRight = 0x006F,
+    CameraRotateLeft = 0x0070,
+    CameraBoundary = 0x0072,
+    NoiseVerySlippery73 = 0x0073,
+    NoiseVerySlippery74 = 0x0074,
+    NoiseVerySlippery = 0x0075,
+    NoCamCollision = 0x0076,
+    NoCamCollision77 = 0x0077,
+    NoCamColVerySlippery = 0x0078,
+    NoCamColSlippery = 0x0079,
+    Switch = 0x007A,
+    VanishCapWalls = 0x007B,
+    PaintingWobbleA6 = 0x00A6,
+    PaintingWobbleA7 = 0x00A7,
+    PaintingWobbleA8 = 0x00A8,
+    PaintingWobbleA9 = 0x00A9,
+    PaintingWobbleAA = 0x00AA,
+    PaintingWobbleAB = 0x00AB,
+    PaintingWobbleAC = 0x00AC,
+    PaintingWobbleAD = 0x00AD,
+    PaintingWobbleAE = 0x00AE,
+    PaintingWobbleAF = 0x00AF,
+    PaintingWobbleB0 = 0x00B0,
+    PaintingWobbleB1 = 0x00B1,
+    PaintingWobbleB2 = 0x00B2,
+    PaintingWobbleB3 = 0x00B3,
+    PaintingWobbleB4 = 0x00B4,
+    PaintingWobbleB5 = 0x00B5,
+    PaintingWobbleB6 = 0x00B6,
+    PaintingWobbleB7 = 0x00B7,
+    PaintingWobbleB8 = 0x00B8,
+    PaintingWobbleB9 = 0x00B9,
+    PaintingWobbleBA = 0x00BA,
+    PaintingWobbleBB = 0x00BB,
+    PaintingWobbleBC = 0x00BC,
+    PaintingWobbleBD = 0x00BD,
+    PaintingWobbleBE = 0x00BE,
+    PaintingWobbleBF = 0x00BF,
+    PaintingWobbleC0 = 0x00C0,
+    PaintingWobbleC1 = 0x00C1,
+    PaintingWobbleC2 = 0x00C2,
+    PaintingWobbleC3 = 0x00C3,
+    PaintingWobbleC4 = 0x00C4,
+    PaintingWobbleC5 = 0x00C5,
+    PaintingWobbleC6 = 0x00C6,
+    PaintingWobbleC7 = 0x00C7,
+    PaintingWobbleC8 = 0x00C8,
+    PaintingWobbleC9 = 0x00C9,
+    PaintingWobbleCA = 0x00CA,
+    PaintingWobbleCB = 0x00CB,
+    PaintingWobbleCC = 0x00CC,
+    PaintingWobbleCD = 0x00CD,
+    PaintingWobbleCE = 0x00CE,
+    PaintingWobbleCF = 0x00CF,
+    PaintingWobbleD0 = 0x00D0,
+    PaintingWobbleD1 = 0x00D1,
+    PaintingWobbleD2 = 0x00D2,
+    PaintingWarpD3 = 0x00D3,
+    PaintingWarpD4 = 0x00D4,
+    PaintingWarpD5 = 0x00D5,
+    PaintingWarpD6 = 0x00D6,
+    PaintingWarpD7 = 0x00D7,
+    PaintingWarpD8 = 0x00D8,
+    PaintingWarpD9 = 0x00D9,
+    PaintingWarpDA = 0x00DA,
+    PaintingWarpDB = 0x00DB,
+    PaintingWarpDC = 0x00DC,
+    PaintingWarpDD = 0x00DD,
+    PaintingWarpDE = 0x00DE,
+    PaintingWarpDF = 0x00DF,
+    PaintingWarpE0 = 0x00E0,
+    PaintingWarpE1 = 0x00E1,
+    PaintingWarpE2 = 0x00E2,
+    PaintingWarpE3 = 0x00E3,
+    PaintingWarpE4 = 0x00E4,
+    PaintingWarpE5 = 0x00E5,
+    PaintingWarpE6 = 0x00E6,
+    PaintingWarpE7 = 0x00E7,
+    PaintingWarpE8 = 0x00E8,
+    PaintingWarpE9 = 0x00E9,
+    PaintingWarpEA = 0x00EA,
+    PaintingWarpEB = 0x00EB,
+    PaintingWarpEC = 0x00EC,
+    PaintingWarpED = 0x00ED,
+    PaintingWarpEE = 0x00EE,
+    PaintingWarpEF = 0x00EF,
+    PaintingWarpF0 = 0x00F0,
+    PaintingWarpF1 = 0x00F1,
+    PaintingWarpF2 = 0x00F2,
+    PaintingWarpF3 = 0x00F3,
+    TtcPainting1 = 0x00F4,
+    TtcPainting2 = 0x00F5,
+    TtcPainting3 = 0x00F6,
+    PaintingWarpF7 = 0x00F7,
+    PaintingWarpF8 = 0x00F8,
+    PaintingWarpF9 = 0x00F9,
+    PaintingWarpFA = 0x00FA,
+    PaintingWarpFB = 0x00FB,
+    PaintingWarpFC = 0x00FC,
+    WobblingWarp = 0x00FD,
+    Trapdoor = 0x00FF,
+}
+
+impl TryFrom<u16> for Surface {
+    type Error = Error;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Surface::from_raw(value).ok_or(Error::UnknownSurface(value))
+    }
+}
+
+impl Surface {
+    /// The raw sm64 value of the surface type
+    pub fn as_u16(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Returns true if the surface is one of the warp types, `Warp`, `InstantWarp*`, `LookUpWarp`, `PaintingWarp*`, `TtcPainting*`, or `WobblingWarp`
+    pub fn is_warp(&self) -> bool {
+        let value = *self as u16;
+
+        matches!(
+            self,
+            Surface::Warp
+                | Surface::InstantWarp1b
+                | Surface::InstantWarp1c
+                | Surface::InstantWarp1d
+                | Surface::InstantWarp1e
+                | Surface::LookUpWarp
+                | Surface::WobblingWarp
+        ) || (Surface::PaintingWarpD3 as u16..=Surface::PaintingWarpFC as u16).contains(&value)
+    }
+
+    fn from_raw(value: u16) -> Option<Self> {
+        let surface = match value {
+            0x0000 => Surface::Default,
+            0x0001 => Surface::Burning,
+            0x0004 => Surface::_0004,
+            0x0005 => Surface::Hangable,
+            0x0009 => Surface::Slow,
+            0x000A => Surface::DeathPlane,
+            0x000B => Surface::CloseCamera,
+            0x000D => Surface::Water,
+            0x000E => Surface::FlowingWater,
+            0x0012 => Surface::Intangible,
+            0x0013 => Surface::VerySlippery,
+            0x0014 => Surface::Slippery,
+            0x0015 => Surface::NotSlippery,
+            0x0016 => Surface::TtmVines,
+            0x001A => Surface::MgrMusic,
+            0x001B => Surface::InstantWarp1b,
+            0x001C => Surface::InstantWarp1c,
+            0x001D => Surface::InstantWarp1d,
+            0x001E => Surface::InstantWarp1e,
+            0x0021 => Surface::ShallowQuicksand,
+            0x0022 => Surface::DeepQuicksand,
+            0x0023 => Surface::InstantQuicksand,
+            0x0024 => Surface::DeepMovingQuicksand,
+            0x0025 => Surface::ShallowMovingQuicksand,
+            0x0026 => Surface::Quicksand,
+            0x0027 => Surface::MovingQuicksand,
+            0x0028 => Surface::WallMisc,
+            0x0029 => Surface::NoiseDefault,
+            0x002A => Surface::NoiseSlippery,
+            0x002C => Surface::HorizontalWind,
+            0x002D => Surface::InstantMovingQuicksand,
+            0x002E => Surface::Ice,
+            0x002F => Surface::LookUpWarp,
+            0x0030 => Surface::Hard,
+            0x0032 => Surface::Warp,
+            0x0033 => Surface::TimerStart,
+            0x0034 => Surface::TimerEnd,
+            0x0035 => Surface::HardSlippery,
+            0x0036 => Surface::HardVerySlippery,
+            0x0037 => Surface::HardNotSlippery,
+            0x0038 => Surface::VerticalWind,
+            0x0065 => Surface::BossFightCamera,
+            0x0066 => Surface::CameraFreeRoam,
+            0x0068 => Surface::Thi3Wallkick,
+            0x0069 => Surface::CameraPlatform,
+            0x006E => Surface::CameraMiddle,
+            0x006F => Surface::CameraRotateRight,
+            0x0070 => Surface::CameraRotateLeft,
+            0x0072 => Surface::CameraBoundary,
+            0x0073 => Surface::NoiseVerySlippery73,
+            0x0074 => Surface::NoiseVerySlippery74,
+            0x0075 => Surface::NoiseVerySlippery,
+            0x0076 => Surface::NoCamCollision,
+            0x0077 => Surface::NoCamCollision77,
+            0x0078 => Surface::NoCamColVerySlippery,
+            0x0079 => Surface::NoCamColSlippery,
+            0x007A => Surface::Switch,
+            0x007B => Surface::VanishCapWalls,
+            0x00A6 => Surface::PaintingWobbleA6,
+            0x00A7 => Surface::PaintingWobbleA7,
+            0x00A8 => Surface::PaintingWobbleA8,
+            0x00A9 => Surface::PaintingWobbleA9,
+            0x00AA => Surface::PaintingWobbleAA,
+            0x00AB => Surface::PaintingWobbleAB,
+            0x00AC => Surface::PaintingWobbleAC,
+            0x00AD => Surface::PaintingWobbleAD,
+            0x00AE => Surface::PaintingWobbleAE,
+            0x00AF => Surface::PaintingWobbleAF,
+            0x00B0 => Surface::PaintingWobbleB0,
+            0x00B1 => Surface::PaintingWobbleB1,
+            0x00B2 => Surface::PaintingWobbleB2,
+            0x00B3 => Surface::PaintingWobbleB3,
+            0x00B4 => Surface::PaintingWobbleB4,
+            0x00B5 => Surface::PaintingWobbleB5,
+            0x00B6 => Surface::PaintingWobbleB6,
+            0x00B7 => Surface::PaintingWobbleB7,
+            0x00B8 => Surface::PaintingWobbleB8,
+            0x00B9 => Surface::PaintingWobbleB9,
+            0x00BA => Surface::PaintingWobbleBA,
+            0x00BB => Surface::PaintingWobbleBB,
+            0x00BC => Surface::PaintingWobbleBC,
+            0x00BD => Surface::PaintingWobbleBD,
+            0x00BE => Surface::PaintingWobbleBE,
+            0x00BF => Surface::PaintingWobbleBF,
+            0x00C0 => Surface::PaintingWobbleC0,
+            0x00C1 => Surface::PaintingWobbleC1,
+            0x00C2 => Surface::PaintingWobbleC2,
+            0x00C3 => Surface::PaintingWobbleC3,
+            0x00C4 => Surface::PaintingWobbleC4,
+            0x00C5 => Surface::PaintingWobbleC5,
+            0x00C6 => Surface::PaintingWobbleC6,
+            0x00C7 => Surface::PaintingWobbleC7,
+            0x00C8 => Surface::PaintingWobbleC8,
+            0x00C9 => Surface::PaintingWobbleC9,
+            0x00CA => Surface::PaintingWobbleCA,
+            0x00CB => Surface::PaintingWobbleCB,
+            0x00CC => Surface::PaintingWobbleCC,
+            0x00CD => Surface::PaintingWobbleCD,
+            0x00CE => Surface::PaintingWobbleCE,
+            0x00CF => Surface::PaintingWobbleCF,
+            0x00D0 => Surface::PaintingWobbleD0,
+            0x00D1 => Surface::PaintingWobbleD1,
+            0x00D2 => Surface::PaintingWobbleD2,
+            0x00D3 => Surface::PaintingWarpD3,
+            0x00D4 => Surface::PaintingWarpD4,
+            0x00D5 => Surface::PaintingWarpD5,
+            0x00D6 => Surface::PaintingWarpD6,
+            0x00D7 => Surface::PaintingWarpD7,
+            0x00D8 => Surface::PaintingWarpD8,
+            0x00D9 => Surface::PaintingWarpD9,
+            0x00DA => Surface::PaintingWarpDA,
+            0x00DB => Surface::PaintingWarpDB,
+            0x00DC => Surface::PaintingWarpDC,
+            0x00DD => Surface::PaintingWarpDD,
+            0x00DE => Surface::PaintingWarpDE,
+            0x00DF => Surface::PaintingWarpDF,
+            0x00E0 => Surface::PaintingWarpE0,
+            0x00E1 => Surface::PaintingWarpE1,
+            0x00E2 => Surface::PaintingWarpE2,
+            0x00E3 => Surface::PaintingWarpE3,
+            0x00E4 => Surface::PaintingWarpE4,
+            0x00E5 => Surface::PaintingWarpE5,
+            0x00E6 => Surface::PaintingWarpE6,
+            0x00E7 => Surface::PaintingWarpE7,
+            0x00E8 => Surface::PaintingWarpE8,
+            0x00E9 => Surface::PaintingWarpE9,
+            0x00EA => Surface::PaintingWarpEA,
+            0x00EB => Surface::PaintingWarpEB,
+            0x00EC => Surface::PaintingWarpEC,
+            0x00ED => Surface::PaintingWarpED,
+            0x00EE => Surface::PaintingWarpEE,
+            0x00EF => Surface::PaintingWarpEF,
+            0x00F0 => Surface::PaintingWarpF0,
+            0x00F1 => Surface::PaintingWarpF1,
+            0x00F2 => Surface::PaintingWarpF2,
+            0x00F3 => Surface::PaintingWarpF3,
+            0x00F4 => Surface::TtcPainting1,
+            0x00F5 => Surface::TtcPainting2,
+            0x00F6 => Surface::TtcPainting3,
+            0x00F7 => Surface::PaintingWarpF7,
+            0x00F8 => Surface::PaintingWarpF8,
+            0x00F9 => Surface::PaintingWarpF9,
+            0x00FA => Surface::PaintingWarpFA,
+            0x00FB => Surface::PaintingWarpFB,
+            0x00FC => Surface::PaintingWarpFC,
+            0x00FD => Surface::WobblingWarp,
+            0x00FF => Surface::Trapdoor,
+            _ => return None,
+        };
+
+        Some(surface)
+    }
+}
+
+/// The action Mario is performing, the values match the `ACT_*` constants of Super Mario 64
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Idle = 0x0C400201,
+    StartSleeping = 0x0C400202,
+    Sleeping = 0x0C000203,
+    WakingUp = 0x0C000204,
+    Panting = 0x0C400205,
+    HoldPantingUnused = 0x08000206,
+    HoldIdle = 0x08000207,
+    HoldHeavyIdle = 0x08000208,
+    StandingAgainstWall = 0x0C400209,
+    Coughing = 0x0C40020A,
+    Shivering = 0x0C40020B,
+    InQuicksand = 0x0002020D,
+    Crouching = 0x0C008220,
+    StartCrouching = 0x0C008221,
+    StopCrouching = 0x0C008222,
+    StartCrawling = 0x0C008223,
+    StopCrawling = 0x0C008224,
+    SlideKickSlideStop = 0x08000225,
+    ShockwaveBounce = 0x00020226,
+    FirstPerson = 0x0C000227,
+    BackflipLandStop = 0x0800022F,
+    JumpLandStop = 0x0C000230,
+    DoubleJumpLandStop = 0x0C000231,
+    FreefallLandStop = 0x0C000232,
+    SideFlipLandStop = 0x0C000233,
+    HoldJumpLandStop = 0x08000234,
+    HoldFreefallLandStop = 0x08000235,
+    AirThrowLand = 0x80000A36,
+    TwirlLand = 0x18800238,
+    LavaBoostLand = 0x08000239,
+    TripleJumpLandStop = 0x0800023A,
+    LongJumpLandStop = 0x0800023B,
+    GroundPoundLand = 0x0080023C,
+    BrakingStop = 0x0C00023D,
+    ButtSlideStop = 0x0C00023E,
+    HoldButtSlideStop = 0x0800043F,
+    Walking = 0x04000440,
+    HoldWalking = 0x00000442,
+    TurningAround = 0x00000443,
+    FinishTurningAround = 0x00000444,
+    Braking = 0x04000445,
+    RidingShellGround = 0x20810446,
+    HoldHeavyWalking = 0x00000447,
+    Crawling = 0x04008448,
+    BurningGround = 0x00020449,
+    Decelerating = 0x0400044A,
+    HoldDecelerating = 0x0000044B,
+    BeginSliding = 0x00000050,
+    HoldBeginSliding = 0x00000051,
+    ButtSlide = 0x00840452,
+    StomachSlide = 0x008C0453,
+    HoldButtSlide = 0x00840454,
+    HoldStomachSlide = 0x008C0455,
+    DiveSlide = 0x00880456,
+    MovePunching = 0x00800457,
+    CrouchSlide = 0x04808459,
+    SlideKickSlide = 0x0080045A,
+    HardBackwardGroundKb = 0x00020460,
+    HardForwardGroundKb = 0x00020461,
+    BackwardGroundKb = 0x00020462,
+    ForwardGroundKb = 0x00020463,
+    SoftBackwardGroundKb = 0x00020464,
+    SoftForwardGroundKb = 0x00020465,
+    GroundBonk = 0x00020466,
+    DeathExitLand = 0x00020467,
+    JumpLand = 0x04000470,
+    FreefallLand = 0x04000471,
+    DoubleJumpLand = 0x04000472,
+    SideFlipLand = 0x04000473,
+    HoldJumpLand = 0x00000474,
+    HoldFreefallLand = 0x00000475,
+    QuicksandJumpLand = 0x00000476,
+    HoldQuicksandJumpLand = 0x00000477,
+    TripleJumpLand = 0x04000478,
+    LongJumpLand = 0x00000479,
+    BackflipLand = 0x0400047A,
+    Jump = 0x03000880,
+    DoubleJump = 0x03000881,
+    TripleJump = 0x01000882,
+    Backflip = 0x01000883,
+    SteepJump = 0x03000885,
+    WallKickAir = 0x03000886,
+    SideFlip = 0x01000887,
+    LongJump = 0x03000888,
+    WaterJump = 0x01000889,
+    Dive = 0x0188088A,
+    Freefall = 0x0100088C,
+    TopOfPoleJump = 0x0300088D,
+    ButtSlideAir = 0x0300088E,
+    FlyingTripleJump = 0x03000894,
+    ShotFromCannon = 0x00880898,
+    Flying = 0x10880899,
+    RidingShellJump = 0x0281089A,
+    RidingShellFall = 0x0081089B,
+    VerticalWind = 0x1008089C,
+    HoldJump = 0x030008A0,
+    HoldFreefall = 0x010008A1,
+    HoldButtSlideAir = 0x010008A2,
+    HoldWaterJump = 0x010008A3,
+    Twirling = 0x108008A4,
+    ForwardRollout = 0x010008A6,
+    AirHitWall = 0x000008A7,
+    RidingHoot = 0x000004A8,
+    GroundPound = 0x008008A9,
+    SlideKick = 0x018008AA,
+    AirThrow = 0x830008AB,
+    JumpKick = 0x018008AC,
+    BackwardRollout = 0x010008AD,
+    CrazyBoxBounce = 0x000008AE,
+    SpecialTripleJump = 0x030008AF,
+    BackwardAirKb = 0x010208B0,
+    ForwardAirKb = 0x010208B1,
+    HardForwardAirKb = 0x010208B2,
+    HardBackwardAirKb = 0x010208B3,
+    BurningJump = 0x010208B4,
+    BurningFall = 0x010208B5,
+    SoftBonk = 0x010208B6,
+    LavaBoost = 0x010208B7,
+    GettingBlown = 0x010208B8,
+    ThrownForward = 0x010208BD,
+    ThrownBackward = 0x010208BE,
+    WaterIdle = 0x380022C0,
+    HoldWaterIdle = 0x380022C1,
+    WaterActionEnd = 0x300022C2,
+    HoldWaterActionEnd = 0x300022C3,
+    Drowning = 0x300032C4,
+    BackwardWaterKb = 0x300222C5,
+    ForwardWaterKb = 0x300222C6,
+    WaterDeath = 0x300032C7,
+    WaterShocked = 0x300222C8,
+    Breaststroke = 0x300024D0,
+    SwimmingEnd = 0x300024D1,
+    FlutterKick = 0x300024D2,
+    HoldBreaststroke = 0x300024D3,
+    HoldSwimmingEnd = 0x300024D4,
+    HoldFlutterKick = 0x300024D5,
+    WaterShellSwimming = 0x300024D6,
+    WaterThrow = 0x300024E0,
+    WaterPunch = 0x300024E1,
+    WaterPlunge = 0x300022E2,
+    CaughtInWhirlpool = 0x300222E3,
+    MetalWaterStanding = 0x080042F0,
+    HoldMetalWaterStanding = 0x080042F1,
+    MetalWaterWalking = 0x000044F2,
+    HoldMetalWaterWalking = 0x000044F3,
+    MetalWaterFalling = 0x000042F4,
+    HoldMetalWaterFalling = 0x000042F5,
+    MetalWaterFallLand = 0x000042F6,
+    HoldMetalWaterFallLand = 0x000042F7,
+    MetalWaterJump = 0x000044F8,
+    HoldMetalWaterJump = 0x000044F9,
+    MetalWaterJumpLand = 0x000044FA,
+    HoldMetalWaterJumpLand = 0x000044FB,
+    Disappeared = 0x00001300,
+    StarDanceExit = 0x00001302,
+    StarDanceWater = 0x00001303,
+    FallAfterStarGrab = 0x00001904,
+    ReadingAutomaticDialog = 0x20001305,
+    ReadingNpcDialog = 0x20001306,
+    StarDanceNoExit = 0x00001307,
+    ReadingSign = 0x00001308,
+    JumboStarCutscene = 0x00001909,
+    WaitingForDialog = 0x0000130A,
+    DebugFreeMove = 0x0000130F,
+    StandingDeath = 0x00021311,
+    QuicksandDeath = 0x00021312,
+    Electrocution = 0x00021313,
+    Suffocation = 0x00021314,
+    DeathOnStomach = 0x00021315,
+    DeathOnBack = 0x00021316,
+    EatenByBubba = 0x00021317,
+    EndPeachCutscene = 0x00001918,
+    CreditsCutscene = 0x00001319,
+    EndWavingCutscene = 0x0000131A,
+    PullingDoor = 0x00001320,
+    PushingDoor = 0x00001321,
+    WarpDoorSpawn = 0x00001322,
+    EmergeFromPipe = 0x00001923,
+    SpawnSpinAirborne = 0x00001924,
+    SpawnSpinLanding = 0x00001325,
+    ExitAirborne = 0x00001926,
+    ExitLandSaveDialog = 0x00001327,
+    DeathExit = 0x00001928,
+    UnusedDeathExit = 0x00001929,
+    FallingDeathExit = 0x0000192A,
+    SpecialExitAirborne = 0x0000192B,
+    SpecialDeathExit = 0x0000192C,
+    FallingExitAirborne = 0x0000192D,
+    UnlockingKeyDoor = 0x0000132E,
+    UnlockingStarDoor = 0x0000132F,
+    EnteringStarDoor = 0x00001331,
+    SpawnNoSpinAirborne = 0x00001932,
+    SpawnNoSpinLanding = 0x00001333,
+    BbhEnterJump = 0x00001934,
+    BbhEnterSpin = 0x00001535,
+    TeleportFadeOut = 0x00001336,
+    TeleportFadeIn = 0x00001337,
+    Shocked = 0x00020338,
+    Squished = 0x00020339,
+    HeadStuckInGround = 0x0002033A,
+    ButtStuckInGround = 0x0002033B,
+    FeetStuckInGround = 0x0002033C,
+    PuttingOnCap = 0x0000133D,
+    HoldingPole = 0x08100340,
+    GrabPoleSlow = 0x00100341,
+    GrabPoleFast = 0x00100342,
+    ClimbingPole = 0x00100343,
+    TopOfPoleTransition = 0x00100344,
+    TopOfPole = 0x00100345,
+    StartHanging = 0x08200348,
+    Hanging = 0x00200349,
+    HangMoving = 0x0020054A,
+    LedgeGrab = 0x0800034B,
+    LedgeClimbSlow1 = 0x0000054C,
+    LedgeClimbSlow2 = 0x0000054D,
+    LedgeClimbDown = 0x0000054E,
+    LedgeClimbFast = 0x0000054F,
+    Grabbed = 0x00020370,
+    InCannon = 0x00001371,
+    TornadoTwirling = 0x10020372,
+    Punching = 0x00800380,
+    PickingUp = 0x00000383,
+    DivePickingUp = 0x00000385,
+    StomachSlideStop = 0x00000386,
+    PlacingDown = 0x00000387,
+    Throwing = 0x80000588,
+    HeavyThrow = 0x80000589,
+    PickingUpBowser = 0x00000390,
+    HoldingBowser = 0x00000391,
+    ReleasingBowser = 0x00000392,
+}
+
+impl Action {
+    /// The action with the raw `ACT_*` value, or `None` if the value isn't one of the actions in `Action`
+    pub fn from_raw(value: u32) -> Option<Self> {
+        let action = match value {
+            0x0C400201 => Action::Idle,
+            0x0C400202 => Action::StartSleeping,
+            0x0C000203 => Action::Sleeping,
+            0x0C000204 => Action::WakingUp,
+            0x0C400205 => Action::Panting,
+            0x08000206 => Action::HoldPantingUnused,
+            0x08000207 => Action::HoldIdle,
+            0x08000208 => Action::HoldHeavyIdle,
+            0x0C400209 => Action::StandingAgainstWall,
+            0x0C40020A => Action::Coughing,
+            0x0C40020B => Action::Shivering,
+            0x0002020D => Action::InQuicksand,
+            0x0C008220 => Action::Crouching,
+            0x0C008221 => Action::StartCrouching,
+            0x0C008222 => Action::StopCrouching,
+            0x0C008223 => Action::StartCrawling,
+            0x0C008224 => Action::StopCrawling,
+            0x08000225 => Action::SlideKickSlideStop,
+            0x00020226 => Action::ShockwaveBounce,
+            0x0C000227 => Action::FirstPerson,
+            0x0800022F => Action::BackflipLandStop,
+            0x0C000230 => Action::JumpLandStop,
+            0x0C000231 => Action::DoubleJumpLandStop,
+            0x0C000232 => Action::FreefallLandStop,
+            0x0C000233 => Action::SideFlipLandStop,
+            0x08000234 => Action::HoldJumpLandStop,
+            0x08000235 => Action::HoldFreefallLandStop,
+            0x80000A36 => Action::AirThrowLand,
+            0x18800238 => Action::TwirlLand,
+            0x08000239 => Action::LavaBoostLand,
+            0x0800023A => Action::TripleJumpLandStop,
+            0x0800023B => Action::LongJumpLandStop,
+            0x0080023C => Action::GroundPoundLand,
+            0x0C00023D => Action::BrakingStop,
+            0x0C00023E => Action::ButtSlideStop,
+            0x0800043F => Action::HoldButtSlideStop,
+            0x04000440 => Action::Walking,
+            0x00000442 => Action::HoldWalking,
+            0x00000443 => Action::TurningAround,
+            0x00000444 => Action::FinishTurningAround,
+            0x04000445 => Action::Braking,
+            0x20810446 => Action::RidingShellGround,
+            0x00000447 => Action::HoldHeavyWalking,
+            0x04008448 => Action::Crawling,
+            0x00020449 => Action::BurningGround,
+            0x0400044A => Action::Decelerating,
+            0x0000044B => Action::HoldDecelerating,
+            0x00000050 => Action::BeginSliding,
+            0x00000051 => Action::HoldBeginSliding,
+            0x00840452 => Action::ButtSlide,
+            0x008C0453 => Action::StomachSlide,
+            0x00840454 => Action::HoldButtSlide,
+            0x008C0455 => Action::HoldStomachSlide,
+            0x00880456 => Action::DiveSlide,
+            0x00800457 => Action::MovePunching,
+            0x04808459 => Action::CrouchSlide,
+            0x0080045A => Action::SlideKickSlide,
+            0x00020460 => Action::HardBackwardGroundKb,
+            0x00020461 => Action::HardForwardGroundKb,
+            0x00020462 => Action::BackwardGroundKb,
+            0x00020463 => Action::ForwardGroundKb,
+            0x00020464 => Action::SoftBackwardGroundKb,
+            0x00020465 => Action::SoftForwardGroundKb,
+            0x00020466 => Action::GroundBonk,
+            0x00020467 => Action::DeathExitLand,
+            0x04000470 => Action::JumpLand,
+            0x04000471 => Action::FreefallLand,
+            0x04000472 => Action::DoubleJumpLand,
+            0x04000473 => Action::SideFlipLand,
+            0x00000474 => Action::HoldJumpLand,
+            0x00000475 => Action::HoldFreefallLand,
+            0x00000476 => Action::QuicksandJumpLand,
+            0x00000477 => Action::HoldQuicksandJumpLand,
+            0x04000478 => Action::TripleJumpLand,
+            0x00000479 => Action::LongJumpLand,
+            0x0400047A => Action::BackflipLand,
+            0x03000880 => Action::Jump,
+            0x03000881 => Action::DoubleJump,
+            0x01000882 => Action::TripleJump,
+            0x01000883 => Action::Backflip,
+            0x03000885 => Action::SteepJump,
+            0x03000886 => Action::WallKickAir,
+            0x01000887 => Action::SideFlip,
+            0x03000888 => Action::LongJump,
+            0x01000889 => Action::WaterJump,
+            0x0188088A => Action::Dive,
+            0x0100088C => Action::Freefall,
+            0x0300088D => Action::TopOfPoleJump,
+            0x0300088E => Action::ButtSlideAir,
+            0x03000894 => Action::FlyingTripleJump,
+            0x00880898 => Action::ShotFromCannon,
+            0x10880899 => Action::Flying,
+            0x0281089A => Action::RidingShellJump,
+            0x0081089B => Action::RidingShellFall,
+            0x1008089C => Action::VerticalWind,
+            0x030008A0 => Action::HoldJump,
+            0x010008A1 => Action::HoldFreefall,
+            0x010008A2 => Action::HoldButtSlideAir,
+            0x010008A3 => Action::HoldWaterJump,
+            0x108008A4 => Action::Twirling,
+            0x010008A6 => Action::ForwardRollout,
+            0x000008A7 => Action::AirHitWall,
+            0x000004A8 => Action::RidingHoot,
+            0x008008A9 => Action::GroundPound,
+            0x018008AA => Action::SlideKick,
+            0x830008AB => Action::AirThrow,
+            0x018008AC => Action::JumpKick,
+            0x010008AD => Action::BackwardRollout,
+            0x000008AE => Action::CrazyBoxBounce,
+            0x030008AF => Action::SpecialTripleJump,
+            0x010208B0 => Action::BackwardAirKb,
+            0x010208B1 => Action::ForwardAirKb,
+            0x010208B2 => Action::HardForwardAirKb,
+            0x010208B3 => Action::HardBackwardAirKb,
+            0x010208B4 => Action::BurningJump,
+            0x010208B5 => Action::BurningFall,
+            0x010208B6 => Action::SoftBonk,
+            0x010208B7 => Action::LavaBoost,
+            0x010208B8 => Action::GettingBlown,
+            0x010208BD => Action::ThrownForward,
+            0x010208BE => Action::ThrownBackward,
+            0x380022C0 => Action::WaterIdle,
+            0x380022C1 => Action::HoldWaterIdle,
+            0x300022C2 => Action::WaterActionEnd,
+            0x300022C3 => Action::HoldWaterActionEnd,
+            0x300032C4 => Action::Drowning,
+            0x300222C5 => Action::BackwardWaterKb,
+            0x300222C6 => Action::ForwardWaterKb,
+            0x300032C7 => Action::WaterDeath,
+            0x300222C8 => Action::WaterShocked,
+            0x300024D0 => Action::Breaststroke,
+            0x300024D1 => Action::SwimmingEnd,
+            0x300024D2 => Action::FlutterKick,
+            0x300024D3 => Action::HoldBreaststroke,
+            0x300024D4 => Action::HoldSwimmingEnd,
+            0x300024D5 => Action::HoldFlutterKick,
+            0x300024D6 => Action::WaterShellSwimming,
+            0x300024E0 => Action::WaterThrow,
+            0x300024E1 => Action::WaterPunch,
+            0x300022E2 => Action::WaterPlunge,
+            0x300222E3 => Action::CaughtInWhirlpool,
+            0x080042F0 => Action::MetalWaterStanding,
+            0x080042F1 => Action::HoldMetalWaterStanding,
+            0x000044F2 => Action::MetalWaterWalking,
+            0x000044F3 => Action::HoldMetalWaterWalking,
+            0x000042F4 => Action::MetalWaterFalling,
+            0x000042F5 => Action::HoldMetalWaterFalling,
+            0x000042F6 => Action::MetalWaterFallLand,
+            0x000042F7 => Action::HoldMetalWaterFallLand,
+            0x000044F8 => Action::MetalWaterJump,
+            0x000044F9 => Action::HoldMetalWaterJump,
+            0x000044FA => Action::MetalWaterJumpLand,
+            0x000044FB => Action::HoldMetalWaterJumpLand,
+            0x00001300 => Action::Disappeared,
+            0x00001302 => Action::StarDanceExit,
+            0x00001303 => Action::StarDanceWater,
+            0x00001904 => Action::FallAfterStarGrab,
+            0x20001305 => Action::ReadingAutomaticDialog,
+            0x20001306 => Action::ReadingNpcDialog,
+            0x00001307 => Action::StarDanceNoExit,
+            0x00001308 => Action::ReadingSign,
+            0x00001909 => Action::JumboStarCutscene,
+            0x0000130A => Action::WaitingForDialog,
+            0x0000130F => Action::DebugFreeMove,
+            0x00021311 => Action::StandingDeath,
+            0x00021312 => Action::QuicksandDeath,
+            0x00021313 => Action::Electrocution,
+            0x00021314 => Action::Suffocation,
+            0x00021315 => Action::DeathOnStomach,
+            0x00021316 => Action::DeathOnBack,
+            0x00021317 => Action::EatenByBubba,
+            0x00001918 => Action::EndPeachCutscene,
+            0x00001319 => Action::CreditsCutscene,
+            0x0000131A => Action::EndWavingCutscene,
+            0x00001320 => Action::PullingDoor,
+            0x00001321 => Action::PushingDoor,
+            0x00001322 => Action::WarpDoorSpawn,
+            0x00001923 => Action::EmergeFromPipe,
+            0x00001924 => Action::SpawnSpinAirborne,
+            0x00001325 => Action::SpawnSpinLanding,
+            0x00001926 => Action::ExitAirborne,
+            0x00001327 => Action::ExitLandSaveDialog,
+            0x00001928 => Action::DeathExit,
+            0x00001929 => Action::UnusedDeathExit,
+            0x0000192A => Action::FallingDeathExit,
+            0x0000192B => Action::SpecialExitAirborne,
+            0x0000192C => Action::SpecialDeathExit,
+            0x0000192D => Action::FallingExitAirborne,
+            0x0000132E => Action::UnlockingKeyDoor,
+            0x0000132F => Action::UnlockingStarDoor,
+            0x00001331 => Action::EnteringStarDoor,
+            0x00001932 => Action::SpawnNoSpinAirborne,
+            0x00001333 => Action::SpawnNoSpinLanding,
+            0x00001934 => Action::BbhEnterJump,
+            0x00001535 => Action::BbhEnterSpin,
+            0x00001336 => Action::TeleportFadeOut,
+            0x00001337 => Action::TeleportFadeIn,
+            0x00020338 => Action::Shocked,
+            0x00020339 => Action::Squished,
+            0x0002033A => Action::HeadStuckInGround,
+            0x0002033B => Action::ButtStuckInGround,
+            0x0002033C => Action::FeetStuckInGround,
+            0x0000133D => Action::PuttingOnCap,
+            0x08100340 => Action::HoldingPole,
+            0x00100341 => Action::GrabPoleSlow,
+            0x00100342 => Action::GrabPoleFast,
+            0x00100343 => Action::ClimbingPole,
+            0x00100344 => Action::TopOfPoleTransition,
+            0x00100345 => Action::TopOfPole,
+            0x08200348 => Action::StartHanging,
+            0x00200349 => Action::Hanging,
+            0x0020054A => Action::HangMoving,
+            0x0800034B => Action::LedgeGrab,
+            0x0000054C => Action::LedgeClimbSlow1,
+            0x0000054D => Action::LedgeClimbSlow2,
+            0x0000054E => Action::LedgeClimbDown,
+            0x0000054F => Action::LedgeClimbFast,
+            0x00020370 => Action::Grabbed,
+            0x00001371 => Action::InCannon,
+            0x10020372 => Action::TornadoTwirling,
+            0x00800380 => Action::Punching,
+            0x00000383 => Action::PickingUp,
+            0x00000385 => Action::DivePickingUp,
+            0x00000386 => Action::StomachSlideStop,
+            0x00000387 => Action::PlacingDown,
+            0x80000588 => Action::Throwing,
+            0x80000589 => Action::HeavyThrow,
+            0x00000390 => Action::PickingUpBowser,
+            0x00000391 => Action::HoldingBowser,
+            0x00000392 => Action::ReleasingBowser,
+            _ => return None,
+        };
+
+        Some(action)
+    }
+}
+
+impl From<Action> for u32 {
+    fn from(action: Action) -> Self {
+        action as u32
+    }
+}
+
+/// An animation Mario can play, the values match the `MARIO_ANIM_*` ids of Super Mario 64
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MarioAnimation {
+    SlowLedgeGrab = 0x00,
+    FallOverBackwards = 0x01,
+    BackwardAirKb = 0x02,
+    DyingOnBack = 0x03,
+    Backflip = 0x04,
+    ClimbUpPole = 0x05,
+    GrabPoleShort = 0x06,
+    GrabPoleSwingPart1 = 0x07,
+    GrabPoleSwingPart2 = 0x08,
+    HandstandIdle = 0x09,
+    HandstandJump = 0x0A,
+    StartHandstand = 0x0B,
+    ReturnFromHandstand = 0x0C,
+    IdleOnPole = 0x0D,
+    APose = 0x0E,
+    SkidOnGround = 0x0F,
+    StopSkid = 0x10,
+    CrouchFromFastLongjump = 0x11,
+    CrouchFromSlowLongjump = 0x12,
+    FastLongjump = 0x13,
+    SlowLongjump = 0x14,
+    AirborneOnStomach = 0x15,
+    WalkWithLightObj = 0x16,
+    RunWithLightObj = 0x17,
+    SlowWalkWithLightObj = 0x18,
+    ShiveringWarmingHand = 0x19,
+    ShiveringReturnToIdle = 0x1A,
+    Shivering = 0x1B,
+    ClimbDownLedge = 0x1C,
+    CreditsWaving = 0x1D,
+    CreditsLookUp = 0x1E,
+    CreditsReturnFromLookUp = 0x1F,
+    CreditsRaiseHand = 0x20,
+    CreditsLowerHand = 0x21,
+    CreditsTakeOffCap = 0x22,
+    CreditsStartWalkLookUp = 0x23,
+    CreditsLookBackThenRun = 0x24,
+    FinalBowserRaiseHandSpin = 0x25,
+    FinalBowserWingCapTakeOff = 0x26,
+    CreditsPeaceSign = 0x27,
+    StandUpFromLavaBoost = 0x28,
+    FireLavaBurn = 0x29,
+    WingCapFly = 0x2A,
+    HangOnOwl = 0x2B,
+    LandOnStomach = 0x2C,
+    AirForwardKb = 0x2D,
+    DyingOnStomach = 0x2E,
+    Suffocating = 0x2F,
+    Coughing = 0x30,
+    ThrowCatchKey = 0x31,
+    DyingFallOver = 0x32,
+    IdleOnLedge = 0x33,
+    FastLedgeGrab = 0x34,
+    HangOnCeiling = 0x35,
+    PutCapOn = 0x36,
+    TakeCapOffThenOn = 0x37,
+    QuicklyPutCapOn = 0x38,
+    HeadStuckInGround = 0x39,
+    GroundPoundLanding = 0x3A,
+    TripleJumpGroundPound = 0x3B,
+    StartGroundPound = 0x3C,
+    GroundPound = 0x3D,
+    BottomStuckInGround = 0x3E,
+    IdleWithLightObj = 0x3F,
+    JumpLandWithLightObj = 0x40,
+    JumpWithLightObj = 0x41,
+    FallLandWithLightObj = 0x42,
+    FallWithLightObj = 0x43,
+    FallFromSlidingWithLightObj = 0x44,
+    SlidingOnBottomWithLightObj = 0x45,
+    StandUpFromSlidingWithLightObj = 0x46,
+    RidingShell = 0x47,
+    Walking = 0x48,
+    ForwardFlip = 0x49,
+    JumpRidingShell = 0x4A,
+    LandFromDoubleJump = 0x4B,
+    DoubleJumpFall = 0x4C,
+    SingleJump = 0x4D,
+    LandFromSingleJump = 0x4E,
+    AirKick = 0x4F,
+    DoubleJumpRise = 0x50,
+    StartForwardSpinning = 0x51,
+    ThrowLightObject = 0x52,
+    FallFromSlideKick = 0x53,
+    BendKnessRidingShell = 0x54,
+    LegsStuckInGround = 0x55,
+    GeneralFall = 0x56,
+    GeneralLand = 0x57,
+    BeingGrabbed = 0x58,
+    GrabHeavyObject = 0x59,
+    SlowLandFromDive = 0x5A,
+    FlyFromCannon = 0x5B,
+    MoveOnWireNetRight = 0x5C,
+    MoveOnWireNetLeft = 0x5D,
+    MissingCap = 0x5E,
+    PullDoorWalkIn = 0x5F,
+    PushDoorWalkIn = 0x60,
+    UnlockDoor = 0x61,
+    StartReachPocket = 0x62,
+    ReachPocket = 0x63,
+    StopReachPocket = 0x64,
+    GroundThrow = 0x65,
+    GroundKick = 0x66,
+    FirstPunch = 0x67,
+    SecondPunch = 0x68,
+    FirstPunchFast = 0x69,
+    SecondPunchFast = 0x6A,
+    PickUpLightObj = 0x6B,
+    Pushing = 0x6C,
+    StartRidingShell = 0x6D,
+    PlaceLightObj = 0x6E,
+    ForwardSpinning = 0x6F,
+    BackwardSpinning = 0x70,
+    Breakdance = 0x71,
+    Running = 0x72,
+    RunningUnused = 0x73,
+    SoftBackKb = 0x74,
+    SoftFrontKb = 0x75,
+    DyingInQuicksand = 0x76,
+    IdleInQuicksand = 0x77,
+    MoveInQuicksand = 0x78,
+    Electrocution = 0x79,
+    Shocked = 0x7A,
+    BackwardKb = 0x7B,
+    ForwardKb = 0x7C,
+    IdleHeavyObj = 0x7D,
+    StandAgainstWall = 0x7E,
+    SidestepLeft = 0x7F,
+    SidestepRight = 0x80,
+    StartSleepIdle = 0x81,
+    StartSleepScratch = 0x82,
+    StartSleepYawn = 0x83,
+    StartSleepSitting = 0x84,
+    SleepIdle = 0x85,
+    SleepStartLying = 0x86,
+    SleepLying = 0x87,
+    Dive = 0x88,
+    SlideDive = 0x89,
+    GroundBonk = 0x8A,
+    StopSlideLightObj = 0x8B,
+    SlideKick = 0x8C,
+    CrouchFromSlideKick = 0x8D,
+    SlideMotionless = 0x8E,
+    StopSlide = 0x8F,
+    FallFromSlide = 0x90,
+    Slide = 0x91,
+    Tiptoe = 0x92,
+    TwirlLand = 0x93,
+    Twirl = 0x94,
+    StartTwirl = 0x95,
+    StopCrouching = 0x96,
+    StartCrouching = 0x97,
+    Crouching = 0x98,
+    Crawling = 0x99,
+    StopCrawling = 0x9A,
+    StartCrawling = 0x9B,
+    SummonStar = 0x9C,
+    ReturnStarApproachDoor = 0x9D,
+    BackwardsWaterKb = 0x9E,
+    SwimWithObjPart1 = 0x9F,
+    SwimWithObjPart2 = 0xA0,
+    FlutterkickWithObj = 0xA1,
+    WaterActionEndWithObj = 0xA2,
+    StopGrabObjWater = 0xA3,
+    WaterIdleWithObj = 0xA4,
+    DrowningPart1 = 0xA5,
+    DrowningPart2 = 0xA6,
+    WaterDying = 0xA7,
+    WaterForwardKb = 0xA8,
+    FallFromWater = 0xA9,
+    SwimPart1 = 0xAA,
+    SwimPart2 = 0xAB,
+    Flutterkick = 0xAC,
+    WaterActionEnd = 0xAD,
+    WaterPickUpObj = 0xAE,
+    WaterGrabObjPart2 = 0xAF,
+    WaterGrabObjPart1 = 0xB0,
+    WaterThrowObj = 0xB1,
+    WaterIdle = 0xB2,
+    WaterStarDance = 0xB3,
+    ReturnFromWaterStarDance = 0xB4,
+    GrabBowser = 0xB5,
+    SwingingBowser = 0xB6,
+    ReleaseBowser = 0xB7,
+    HoldingBowser = 0xB8,
+    HeavyThrow = 0xB9,
+    WalkPanting = 0xBA,
+    WalkWithHeavyObj = 0xBB,
+    TurningPart1 = 0xBC,
+    TurningPart2 = 0xBD,
+    SlideflipLand = 0xBE,
+    Slideflip = 0xBF,
+    TripleJumpLand = 0xC0,
+    TripleJump = 0xC1,
+    FirstPerson = 0xC2,
+    IdleHeadLeft = 0xC3,
+    IdleHeadRight = 0xC4,
+    IdleHeadCenter = 0xC5,
+    HandstandLeft = 0xC6,
+    HandstandRight = 0xC7,
+    WakeFromSleep = 0xC8,
+    WakeFromLying = 0xC9,
+    StartTiptoe = 0xCA,
+    Slidejump = 0xCB,
+    StartWallKick = 0xCC,
+    StarDance = 0xCD,
+    ReturnFromStarDance = 0xCE,
+    ForwardSpinningFlip = 0xCF,
+    TripleJumpFly = 0xD0,
+}
+
+impl From<MarioAnimation> for i32 {
+    fn from(animation: MarioAnimation) -> Self {
+        animation as i32
+    }
+}
+
+// libsm64's state is global so tests that use it must not run in parallel
+#[cfg(test)]
+static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(test)]
+fn test_rom() -> std::fs::File {
+    let rom = std::env::var("SM64_ROM_PATH")
+        .expect("Path to SM64 rom must be proivided in 'SM64_ROM_PATH' env var");
+    std::fs::File::open(rom).unwrap()
+}
+
+#[cfg(test)]
+fn test_mario() -> (std::sync::MutexGuard<'static, ()>, Sm64, Mario) {
+    let lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut sm64 = Sm64::new(test_rom()).unwrap();
+
+    let floor = LevelTriangle::quad(
+        Surface::Default,
+        Terrain::Grass,
+        [
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: -1000,
+            },
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: -1000,
+            },
+        ],
+    );
+    sm64.load_level_geometry(&floor);
+
+    let mario = sm64.create_mario(0, 0, 0).unwrap();
+
+    (lock, sm64, mario)
+}
+
+#[test]
+fn basic_loading() {
+    let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let rom = std::env::var("SM64_ROM_PATH")
+        .expect("Path to SM64 rom must be proivided in 'SM64_ROM_PATH' env var");
+    let rom = std::fs::File::open(rom).unwrap();
+    let mut sm64 = Sm64::new(rom).unwrap();
+    let mario = sm64.create_mario(1, 2, 3);
+
+    match mario {
+        Err(Error::InvalidMarioPosition) => (),
+        _ => panic!("Expected InvalidMarioPosition error"),
+    }
+}
+
+#[test]
+fn correct_repr() {
+    assert_eq!(
+        std::mem::size_of::<LevelTriangle>(),
+        std::mem::size_of::<libsm64_sys::SM64Surface>()
+    );
+    assert_eq!(
+        std::mem::size_of::<RawLevelTriangle>(),
+        std::mem::size_of::<libsm64_sys::SM64Surface>()
+    );
+
+    let tri = LevelTriangle {
+        kind: Surface::Default,
+        force: 333,
+        terrain: Terrain::Grass,
+        vertices: (
+            Point3 { x: 1, y: 2, z: 3 },
+            Point3 { x: 4, y: 5, z: 6 },
+            Point3 { x: 7, y: 8, z: 9 },
+        ),
+    };
+
+    let c_tri = libsm64_sys::SM64Surface {
+        type_: 0,
+        force: 333,
+        terrain: 0,
+        vertices: [[1, 2, 3], [4, 5, 6], [7, 8, 9]],
+    };
+
+    let my_c_tri = unsafe { std::mem::transmute::<_, libsm64_sys::SM64Surface>(tri) };
+
+    assert_eq!(c_tri.type_, my_c_tri.type_);
+    assert_eq!(c_tri.force, my_c_tri.force);
+    assert_eq!(c_tri.terrain, my_c_tri.terrain);
+    assert_eq!(c_tri.vertices, my_c_tri.vertices);
+}
+
+#[test]
+fn surface_force_round_trip() {
+    let force = SurfaceForce {
+        direction: std::f32::consts::FRAC_PI_2,
+        strength: 2,
+    };
+
+    assert_eq!(force.to_raw(), 0x0240);
+    assert_eq!(SurfaceForce::from_raw(force.to_raw()), force);
+
+    let force = SurfaceForce {
+        direction: -std::f32::consts::FRAC_PI_2,
+        strength: 0,
+    };
+
+    assert_eq!(force.to_raw(), 0x00c0);
+    assert_eq!(SurfaceForce::from_raw(force.to_raw()), force);
+}
+
+#[test]
+fn geometry_diff() {
+    let mut prev = MarioGeometry::new();
+    prev.num_triangles = 2;
+    let mut geo = MarioGeometry::new();
+    geo.num_triangles = 2;
+
+    assert!(geo.diff(&prev).is_empty());
+
+    geo.position[1].x = 1.0;
+    geo.position[2].x = 1.0;
+    geo.uv[5].y = 1.0;
+
+    assert_eq!(geo.diff(&prev), ChangedRanges::Ranges(vec![1..3, 5..6]));
+
+    geo.num_triangles = 1;
+
+    assert_eq!(geo.diff(&prev), ChangedRanges::All);
+}
+
+#[test]
+fn quantize_f64_triangle() {
+    let p = |x, y, z| Point3 { x, y, z };
+
+    let tri = LevelTriangle::from_f64(
+        Surface::Default,
+        Terrain::Grass,
+        [p(0.0, 0.0, 0.0), p(1.26, 0.0, 0.0), p(0.0, 0.0, -1000.0)],
+        100.0,
+    )
+    .unwrap();
+
+    assert_eq!(tri.vertices.1.x, 126);
+    assert_eq!(tri.vertices.2.z, i16::MIN);
+
+    let degenerate = LevelTriangle::from_f64(
+        Surface::Default,
+        Terrain::Grass,
+        [p(0.0, 0.0, 0.0), p(0.001, 0.0, 0.0), p(0.0, 0.0, 0.001)],
+        100.0,
+    );
+
+    assert!(matches!(degenerate, Err(Error::DegenerateTriangle)));
+}
+
+#[test]
+fn geometry_clone() {
+    let mut geo = MarioGeometry::new();
+    geo.position[0] = Point3 {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+    geo.update(1);
+
+    let cloned = geo.clone();
+    let snapshot = geo.snapshot();
+    assert_eq!(snapshot.positions().len(), 3);
+    assert_eq!(snapshot.position.len(), 3);
+
+    geo.position[0] = Point3::default();
+    geo.update(2);
+
+    for copy in [&cloned, &snapshot] {
+        assert_eq!(copy.triangles().count(), 1);
+        assert_eq!(
+            copy.positions()[0],
+            Point3 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0,
+            }
+        );
+    }
+    assert_eq!(geo.triangles().count(), 2);
+}
+
+#[test]
+fn geometry_transformed() {
+    let mut geo = MarioGeometry::new();
+    geo.num_triangles = 1;
+    geo.position[1] = Point3 {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+    geo.position[2] = Point3 {
+        x: 5.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    geo.normal[0] = Point3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    let shear = [
+        [1.0, 1.0, 0.0, 10.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    let sheared = geo.transformed(&shear);
+
+    assert_eq!(
+        sheared.position[1],
+        Point3 {
+            x: 13.0,
+            y: 2.0,
+            z: 3.0
+        }
+    );
+    assert!((sheared.normal[0].x - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    assert!((sheared.normal[0].y + std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+    let mirror = [
+        [-1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    let mirrored = geo.transformed(&mirror);
+
+    assert_eq!(
+        mirrored.position[1],
+        Point3 {
+            x: -5.0,
+            y: 0.0,
+            z: 0.0
+        }
+    );
+    assert_eq!(
+        mirrored.normal[0],
+        Point3 {
+            x: -1.0,
+            y: 0.0,
+            z: 0.0
+        }
+    );
+}
+
+#[test]
+fn tick_clock() {
+    let mut clock = TickClock::new();
+
+    assert_eq!(clock.advance(Duration::from_secs(1)), TICK_RATE_HZ);
+    assert_eq!(clock.advance(TICK_DURATION / 2), 0);
+    assert!((clock.alpha() - 0.5).abs() < 1e-3);
+
+    clock.set_time_scale(0.5);
+
+    assert_eq!(clock.advance(Duration::from_secs(1)), TICK_RATE_HZ / 2);
+
+    for time_scale in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN, -1.0] {
+        clock.set_time_scale(time_scale);
+        assert_eq!(clock.time_scale(), 0.0);
+        assert_eq!(clock.advance(Duration::from_secs(1)), 0);
+    }
+}
+
+#[test]
+fn level_scale_validation() {
+    let scale = LevelScale::new(100.0);
+
+    assert!(scale.validate(300.0, 0.01).is_ok());
+    assert!(matches!(
+        scale.validate(300.0, 0.001),
+        Err(Error::InvalidScale(_))
+    ));
+    assert!(matches!(
+        scale.validate(400.0, 0.01),
+        Err(Error::InvalidScale(_))
+    ));
+}
+
+#[test]
+fn geometry_scaled_about() {
+    let mut geo = MarioGeometry::new();
+    geo.position[0] = Point3 {
+        x: 2.0,
+        y: 1.0,
+        z: 1.0,
+    };
+    geo.num_triangles = 1;
+
+    let scaled = geo.scaled_about(
+        Point3 {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        },
+        3.0,
+    );
+
+    assert_eq!(
+        scaled.positions()[0],
+        Point3 {
+            x: 4.0,
+            y: 1.0,
+            z: 1.0,
+        }
+    );
+}
+
+#[test]
+fn held_button() {
+    let mut button = HeldButton::new();
+
+    button.press(2);
+    assert!(button.tick());
+    button.press(2);
+    assert!(!button.tick());
+    assert!(button.tick());
+    assert!(button.tick());
+    assert!(!button.tick());
+}
+
+#[test]
+fn action_values() {
+    assert_eq!(u32::from(Action::Idle), 0x0C400201);
+    assert_eq!(u32::from(Action::WaterIdle), 0x380022C0);
+    assert_eq!(u32::from(Action::Jump), 0x03000880);
+
+    for action in [
+        Action::Idle,
+        Action::WaterIdle,
+        Action::Jump,
+        Action::Throwing,
+    ] {
+        assert_eq!(Action::from_raw(action.into()), Some(action));
+    }
+    assert_eq!(Action::from_raw(0x0000_0000), None);
+}
+
+#[test]
+fn set_position() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    let state = mario.tick(MarioInput::default());
+    mario.set_position(Point3 {
+        y: state.position.y + 100.0,
+        ..state.position
+    });
+    let moved = mario.tick(MarioInput::default());
+
+    assert!(moved.position.y > state.position.y + 90.0);
+}
+
+#[test]
+fn handedness_mirror() {
+    let mut geo = MarioGeometry::new();
+    geo.position[0] = Point3 {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+    geo.position[1] = Point3 {
+        x: 4.0,
+        y: 5.0,
+        z: 6.0,
+    };
+    geo.normal[0] = Point3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    geo.mirror(1);
+    geo.update(1);
+
+    assert_eq!(
+        geo.positions()[0],
+        Handedness::Left.convert(Point3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0
+        })
+    );
+    assert_eq!(geo.positions()[2].x, -4.0);
+    assert_eq!(geo.normals()[0].x, -1.0);
+
+    let input = Handedness::Left.convert_input(MarioInput {
+        cam_look_x: 0.5,
+        stick_x: 1.0,
+        stick_y: 1.0,
+        ..Default::default()
+    });
+
+    assert_eq!(input.cam_look_x, -0.5);
+    assert_eq!(input.stick_x, -1.0);
+    assert_eq!(input.stick_y, 1.0);
+}
+
+#[test]
+fn set_forward_velocity() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    let state = mario.tick(MarioInput::default());
+    mario.set_position(Point3 {
+        y: state.position.y + 500.0,
+        ..state.position
+    });
+    mario.set_action(Action::Freefall);
+    mario.set_forward_velocity(40.0);
+    let state = mario.tick(MarioInput::default());
+
+    let expected = (state.face_angle.sin(), state.face_angle.cos());
+    let speed = (state.velocity.x * state.velocity.x + state.velocity.z * state.velocity.z).sqrt();
+
+    assert!(speed > 0.0);
+    assert!((state.velocity.x / speed - expected.0).abs() < 0.01);
+    assert!((state.velocity.z / speed - expected.1).abs() < 0.01);
+}
+
+#[test]
+fn set_face_angle() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    mario.set_face_angle(0.5);
+    let state = mario.tick(MarioInput::default());
+    assert!((state.face_angle - 0.5).abs() < 0.01);
+
+    mario.set_face_angle(0.5 + std::f32::consts::PI);
+    let state = mario.tick(MarioInput::default());
+    assert!(state.face_angle < 0.0);
+    assert!((state.face_angle - (0.5 - std::f32::consts::PI)).abs() < 0.01);
+}
+
+#[test]
+fn rumble() {
+    assert_eq!(
+        action_rumble(Action::GroundPoundLand),
+        Some(MarioEvent::Rumble {
+            ticks: 5,
+            strength: 0.8
+        })
+    );
+    assert_eq!(action_rumble(Action::Idle), None);
+
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    for _ in 0..30 {
+        mario.tick(MarioInput::default());
+    }
+    let state = mario.tick(MarioInput::default());
+    mario.take_damage(
+        2,
+        Point3 {
+            x: state.position.x + 100.0,
+            ..state.position
+        },
+    );
+
+    let mut rumbles = 0;
+    for _ in 0..30 {
+        mario.tick(MarioInput::default());
+        rumbles += mario
+            .events()
+            .iter()
+            .filter(|event| matches!(event, MarioEvent::Rumble { .. }))
+            .count();
+    }
+    assert!(rumbles > 0);
+}
+
+#[test]
+fn take_damage() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    let state = mario.tick(MarioInput::default());
+    mario.take_damage(
+        1,
+        Point3 {
+            x: state.position.x + 100.0,
+            ..state.position
+        },
+    );
+
+    let mut damaged = state;
+    for _ in 0..10 {
+        damaged = mario.tick(MarioInput::default());
+    }
+
+    assert_eq!(damaged.health, state.health - 0x100);
+}
+
+#[test]
+fn kill() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    assert!(!mario.tick(MarioInput::default()).is_dead());
+
+    mario.kill();
+
+    let mut dead = false;
+    for _ in 0..5 {
+        dead |= mario.tick(MarioInput::default()).is_dead();
+    }
+
+    assert!(dead);
+}
+
+#[test]
+fn interact_cap() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    mario.interact_cap(Cap::Wing, 0, false);
+    for _ in 0..5 {
+        mario.tick(MarioInput::default());
+    }
+    mario.extend_cap(30);
+    let state = mario.tick(MarioInput::default());
+
+    assert!(state.flags.has_wing_cap());
+}
+
+#[test]
+fn set_invincibility() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    mario.set_invincibility(30);
+    assert!(mario.tick(MarioInput::default()).invincibility_timer > 0);
+
+    mario.set_invincibility(-1);
+    assert_eq!(mario.tick(MarioInput::default()).invincibility_timer, 0);
+}
+
+#[test]
+fn spawn_action_group() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    let mut state = mario.tick(MarioInput::default());
+    for _ in 0..60 {
+        state = mario.tick(MarioInput::default());
+    }
+
+    assert_eq!(state.action_group(), ActionGroup::Stationary);
+}
+
+#[cfg(feature = "counters")]
+#[test]
+fn spawn_counters() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    let state = mario.tick(MarioInput::default());
+
+    assert!(state.num_lives > 0);
+    assert_eq!(state.num_coins, 0);
+    assert_eq!(state.num_stars, 0);
+}
+
+#[test]
+fn debug_handler() {
+    let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let handler_messages = messages.clone();
+    *DEBUG_HANDLER.lock().unwrap() = Some(Box::new(move |message: &str| {
+        handler_messages.lock().unwrap().push(message.to_string())
+    }));
+
+    unsafe {
+        debug_print(std::ptr::null());
+        debug_print(c"mario".as_ptr());
+        debug_print(c"\xffmario".as_ptr());
+    }
+
+    *DEBUG_HANDLER.lock().unwrap() = Some(Box::new(|message: &str| {
+        unsafe { debug_print(c"nested".as_ptr()) };
+        panic!("{}", message);
+    }));
+    unsafe { debug_print(c"panic".as_ptr()) };
+    *DEBUG_HANDLER.lock().unwrap() = None;
+
+    assert_eq!(
+        *messages.lock().unwrap(),
+        vec!["mario".to_string(), "\u{FFFD}mario".to_string()]
+    );
+}
+
+#[test]
+fn from_path_error() {
+    match Sm64::from_path("missing-rom.z64") {
+        Err(Error::Io(err)) => assert!(err.to_string().contains("missing-rom.z64")),
+        _ => panic!("Expected Io error"),
+    }
+}
+
+#[test]
+fn from_bytes_invalid_rom() {
+    match Sm64::from_bytes(vec![0; 1024]) {
+        Err(Error::InvalidRom(_)) => (),
+        _ => panic!("Expected InvalidRom error"),
+    }
+}
+
+#[test]
+fn rom_byte_order() {
+    let z64 = vec![0x80, 0x37, 0x12, 0x40, 0x01, 0x02, 0x03, 0x04];
+    let mut v64 = vec![0x37, 0x80, 0x40, 0x12, 0x02, 0x01, 0x04, 0x03];
+    let mut n64 = vec![0x40, 0x12, 0x37, 0x80, 0x04, 0x03, 0x02, 0x01];
+
+    normalize_rom_byte_order(&mut v64);
+    normalize_rom_byte_order(&mut n64);
+
+    assert_eq!(v64, z64);
+    assert_eq!(n64, z64);
+
+    let mut unchanged = z64.clone();
+    normalize_rom_byte_order(&mut unchanged);
+
+    assert_eq!(unchanged, z64);
+}
+
+#[test]
+fn rom_regions() {
+    let table = [
+        ("9bef1128717f958171a4afac3ed78ee2bb4e86ce", RomRegion::Us),
+        ("8a20a5c83d6ceb0f0506cfc9fa20d8f438cafe51", RomRegion::Jp),
+        ("4ac5721683d0e0b6bbb561b58a71740845dceea9", RomRegion::Eu),
+    ];
+
+    for (hash, region) in table {
+        assert_eq!(RomRegion::from_hash(hash), Some(region));
+    }
+    assert_eq!(
+        RomRegion::from_hash("0000000000000000000000000000000000000000"),
+        None
+    );
+}
+
+#[test]
+fn single_instance() {
+    let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let sm64 = Sm64::new(test_rom()).unwrap();
+    assert!(matches!(
+        Sm64::new(test_rom()),
+        Err(Error::AlreadyInitialized)
+    ));
+
+    drop(sm64);
+    assert!(Sm64::new(test_rom()).is_ok());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let tri = LevelTriangle {
+        kind: Surface::Burning,
+        force: 0,
+        terrain: Terrain::Snow,
+        vertices: (
+            Point3 { x: 1, y: 2, z: 3 },
+            Point3 { x: 4, y: 5, z: 6 },
+            Point3 { x: 7, y: 8, z: 9 },
         ),
     };
 
-    let c_tri = libsm64_sys::SM64Surface {
-        type_: 0,
-        force: 333,
-        terrain: 0,
-        vertices: [[1, 2, 3], [4, 5, 6], [7, 8, 9]],
+    let json = serde_json::to_string(&tri).unwrap();
+    assert!(json.contains("\"Burning\""));
+    assert!(json.contains("\"Snow\""));
+
+    let round_trip: LevelTriangle = serde_json::from_str(&json).unwrap();
+    assert_eq!(round_trip.kind, tri.kind);
+    assert_eq!(round_trip.vertices, tri.vertices);
+
+    let state = MarioState {
+        position: Point3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        },
+        health: 0x880,
+        flags: MarioFlags::WING_CAP,
+        ..Default::default()
+    };
+    let json = serde_json::to_string(&state).unwrap();
+    let round_trip: MarioState = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(round_trip.position, state.position);
+    assert_eq!(round_trip.health, state.health);
+    assert_eq!(round_trip.flags, state.flags);
+}
+
+#[cfg(feature = "glam")]
+#[test]
+fn glam_conversions() {
+    let v = glam::Vec3::new(1.5, -2.25, 1e-8);
+    let p: Point3<f32> = v.into();
+    assert_eq!(glam::Vec3::from(p), v);
+
+    let v = glam::Vec2::new(0.1, f32::MAX);
+    let p: Point2<f32> = v.into();
+    assert_eq!(glam::Vec2::from(p), v);
+
+    let transform = SurfaceTransform {
+        position: Point3::default(),
+        euler_rotation: Point3 {
+            x: 0.0,
+            y: 90.0,
+            z: 0.0,
+        },
+    };
+    let forward = transform.rotation() * glam::Vec3::Z;
+    assert!((forward - glam::Vec3::X).length() < 1e-6);
+
+    let transform = SurfaceTransform {
+        position: Point3 {
+            x: 10.0,
+            y: -20.0,
+            z: 30.0,
+        },
+        euler_rotation: Point3 {
+            x: 30.0,
+            y: 45.0,
+            z: -60.0,
+        },
+    };
+    let affine = glam::Affine3A::from(transform);
+    let point = glam::Vec3::new(1.0, 2.0, 3.0);
+    let expected = transform.rotation() * point + glam::Vec3::from(transform.position);
+    assert!((affine.transform_point3(point) - expected).length() < 1e-4);
+
+    let scaled = glam::Affine3A::from_scale(glam::Vec3::splat(2.0)) * affine;
+    for round_trip in [
+        SurfaceTransform::from(affine),
+        SurfaceTransform::from(scaled),
+    ] {
+        assert!((round_trip.euler_rotation.x - 30.0).abs() < 1e-3);
+        assert!((round_trip.euler_rotation.y - 45.0).abs() < 1e-3);
+        assert!((round_trip.euler_rotation.z + 60.0).abs() < 1e-3);
+    }
+    let round_trip = SurfaceTransform::from(affine);
+    assert!(
+        (glam::Vec3::from(round_trip.position) - glam::Vec3::new(10.0, -20.0, 30.0)).length()
+            < 1e-4
+    );
+}
+
+#[cfg(feature = "mint")]
+#[test]
+fn mint_conversions() {
+    let p = mint::Point3 {
+        x: 1.5f32,
+        y: -2.0,
+        z: 3.25,
+    };
+    let converted: Point3<f32> = p.into();
+    assert_eq!(mint::Point3::from(converted), p);
+
+    let p = mint::Point3 {
+        x: i16::MIN,
+        y: 0i16,
+        z: i16::MAX,
+    };
+    let converted: Point3<i16> = p.into();
+    assert_eq!(mint::Point3::from(converted), p);
+
+    let p = mint::Point2 { x: 0.5f32, y: 0.25 };
+    let converted: Point2<f32> = p.into();
+    assert_eq!(mint::Point2::from(converted), p);
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn bytemuck_cast() {
+    let mut geo = MarioGeometry::new();
+    geo.num_triangles = 1;
+    geo.position[1] = Point3 {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+
+    let floats: &[f32] = bytemuck::cast_slice(geo.positions());
+    assert_eq!(floats.len(), 9);
+    assert_eq!(&floats[3..6], &[1.0, 2.0, 3.0]);
+
+    let vertices: Vec<MarioVertex> = geo.vertices().collect();
+    let floats: &[f32] = bytemuck::cast_slice(&vertices);
+    assert_eq!(floats.len(), 3 * 11);
+    assert_eq!(&floats[11..14], &[1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn geometry_interleaved() {
+    assert_eq!(std::mem::size_of::<MarioVertex>(), 44);
+    assert_eq!(std::mem::size_of::<MarioVertexPacked>(), 44);
+
+    let mut geo = MarioGeometry::new();
+    geo.position[1] = Point3 {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+    geo.uv[2] = Point2 { x: 0.5, y: 0.25 };
+    geo.update(1);
+
+    let vertices: Vec<MarioVertexPacked> = geo.vertices().map(Into::into).collect();
+    assert_eq!(geo.interleaved(), vertices.as_slice());
+    assert_eq!(geo.interleaved()[1].position, [1.0, 2.0, 3.0]);
+    assert_eq!(geo.interleaved()[2].uv, [0.5, 0.25]);
+    let buffer = geo.interleaved().as_ptr();
+
+    // The buffer is refilled in place rather than reallocated
+    geo.position[1].x = 4.0;
+    geo.update(1);
+    assert_eq!(geo.interleaved()[1].position, [4.0, 2.0, 3.0]);
+    assert_eq!(geo.interleaved().as_ptr(), buffer);
+
+    geo.update(0);
+    assert!(geo.interleaved().is_empty());
+
+    let mut snapshot = geo.snapshot();
+    geo.update(1);
+    assert_eq!(geo.interleaved().as_ptr(), buffer);
+    assert!(snapshot.interleaved().is_empty());
+}
+
+#[test]
+fn geometry_indexed() {
+    let mut geo = MarioGeometry::new();
+    geo.position[1] = Point3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    geo.position[2] = Point3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    geo.position[4] = Point3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    geo.position[5] = Point3 {
+        x: 1.0,
+        y: 1.0,
+        z: 0.00001,
+    };
+    geo.update(2);
+
+    let (vertices, indices) = geo.indexed();
+    assert_eq!(vertices.len(), 4);
+
+    let reconstructed: Vec<_> = indices
+        .chunks_exact(3)
+        .map(|i| {
+            (
+                vertices[i[0] as usize],
+                vertices[i[1] as usize],
+                vertices[i[2] as usize],
+            )
+        })
+        .collect();
+    assert_eq!(reconstructed, geo.triangles().collect::<Vec<_>>());
+
+    geo.position[0] = Point3 {
+        x: 1.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    geo.update(2);
+    assert_eq!(geo.indexed().0.len(), 5);
+    assert_eq!(geo.indexed_with_epsilon(0.001).0.len(), 4);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn texture_save_png() {
+    let texture = Texture {
+        data: &[0xff; 4 * 3 * 2],
+        width: 3,
+        height: 2,
+    };
+
+    let path = std::env::temp_dir().join("libsm64_texture_save_png.png");
+    texture.save_png(&path).unwrap();
+
+    let image = image::open(&path).unwrap().into_rgba8();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(image.dimensions(), (3, 2));
+    assert_eq!(image.pixels().count(), 6);
+    assert_eq!(image.into_raw(), texture.data);
+}
+
+#[test]
+fn geometry_to_obj() {
+    let mut geo = MarioGeometry::new();
+    geo.position[4] = Point3 {
+        x: 0.1,
+        y: 1.0 / 3.0,
+        z: -7.5,
+    };
+    geo.update(2);
+
+    let obj = geo.to_obj_with_mtl("mario.mtl", "mario");
+    let count = |prefix: &str| obj.lines().filter(|l| l.starts_with(prefix)).count();
+
+    assert_eq!(count("f "), 2);
+    assert_eq!(count("v "), 6);
+    assert_eq!(count("vn "), 6);
+    assert_eq!(count("vt "), 6);
+    assert!(obj.lines().any(|l| l == "mtllib mario.mtl"));
+    assert!(obj.lines().any(|l| l == "f 4/4/4 5/5/5 6/6/6"));
+
+    let v: Vec<f32> = obj
+        .lines()
+        .filter(|l| l.starts_with("v "))
+        .nth(4)
+        .unwrap()
+        .split_whitespace()
+        .skip(1)
+        .map(|c| c.parse().unwrap())
+        .collect();
+    assert_eq!(v[..3], [0.1, 1.0 / 3.0, -7.5]);
+}
+
+#[cfg(feature = "gltf")]
+#[test]
+fn geometry_to_gltf() {
+    let mut geo = MarioGeometry::new();
+    geo.position[1] = Point3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    geo.position[2] = Point3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    geo.update(1);
+
+    let texture = Texture {
+        data: &[0xff; 4 * 2 * 2],
+        width: 2,
+        height: 2,
+    };
+    let glb = geo.to_gltf(&texture);
+
+    assert_eq!(&glb[..4], b"glTF");
+
+    let gltf = gltf::Gltf::from_slice(&glb).unwrap();
+    let primitive = gltf.meshes().next().unwrap().primitives().next().unwrap();
+
+    assert_eq!(primitive.attributes().count(), 4);
+    assert_eq!(
+        primitive.get(&gltf::Semantic::Colors(0)).unwrap().count(),
+        3
+    );
+    assert_eq!(gltf.images().count(), 1);
+}
+
+#[test]
+fn geometry_tangents() {
+    let mut geo = MarioGeometry::new();
+    let up = Point3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    for i in 0..6 {
+        geo.normal[i] = up;
+    }
+    geo.position[1] = Point3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    geo.position[2] = Point3 {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+    geo.uv[1] = Point2 { x: 1.0, y: 0.0 };
+    geo.uv[2] = Point2 { x: 0.0, y: 1.0 };
+    // The second triangle has no uv area
+    geo.position[4] = geo.position[1];
+    geo.position[5] = geo.position[2];
+    geo.position[3] = Point3 {
+        x: 1.0,
+        y: 0.0,
+        z: 1.0,
+    };
+    geo.update(2);
+
+    let tangents = geo.tangents();
+    assert_eq!(tangents.len(), 6);
+
+    for (t, n) in tangents.iter().zip(geo.normals()) {
+        let dot = t[0] * n.x + t[1] * n.y + t[2] * n.z;
+        let len = (t[0] * t[0] + t[1] * t[1] + t[2] * t[2]).sqrt();
+
+        assert!(dot.abs() < 1e-5);
+        assert!((len - 1.0).abs() < 1e-5);
+        assert!(t[3] == 1.0 || t[3] == -1.0);
+    }
+    assert!((tangents[0][0] - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn level_scale_conversion() {
+    let scale = LevelScale::new(100.0);
+
+    let p = scale
+        .world_to_sm64(Point3 {
+            x: 0.004,
+            y: 0.006,
+            z: -1.5,
+        })
+        .unwrap();
+    assert_eq!(
+        p,
+        Point3 {
+            x: 0,
+            y: 1,
+            z: -150
+        }
+    );
+
+    assert!(matches!(
+        scale.world_to_sm64(Point3 {
+            x: 327.68,
+            y: 0.0,
+            z: 0.0,
+        }),
+        Err(Error::CoordinateOutOfRange(_))
+    ));
+    assert!(scale
+        .world_to_sm64(Point3 {
+            x: 327.67,
+            y: -327.68,
+            z: 0.0,
+        })
+        .is_ok());
+
+    let world = scale.sm64_to_world(Point3 {
+        x: 150.0,
+        y: 0.0,
+        z: -50.0,
+    });
+    assert_eq!(
+        world,
+        Point3 {
+            x: 1.5,
+            y: 0.0,
+            z: -0.5,
+        }
+    );
+
+    let tri = LevelTriangle::from_world(
+        Surface::Default,
+        Terrain::Grass,
+        [
+            Point3 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            Point3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            Point3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        ],
+        scale,
+    )
+    .unwrap();
+    assert_eq!(tri.vertices.1, Point3 { x: 0, y: 0, z: 100 });
+}
+
+#[test]
+fn level_triangle_quad() {
+    let normal = |tri: &LevelTriangle| {
+        let [a, b, c] = tri.vertices_f32(1.0);
+        let ab = [b.x - a.x, b.y - a.y, b.z - a.z];
+        let bc = [c.x - b.x, c.y - b.y, c.z - b.z];
+        [
+            ab[1] * bc[2] - ab[2] * bc[1],
+            ab[2] * bc[0] - ab[0] * bc[2],
+            ab[0] * bc[1] - ab[1] * bc[0],
+        ]
     };
 
-    let my_c_tri = unsafe { std::mem::transmute::<_, libsm64_sys::SM64Surface>(tri) };
+    let corners = [
+        Point3 { x: 0, y: 0, z: 0 },
+        Point3 { x: 0, y: 0, z: 10 },
+        Point3 { x: 10, y: 0, z: 10 },
+        Point3 { x: 10, y: 0, z: 0 },
+    ];
+    let single = LevelTriangle::new(
+        Surface::Default,
+        Terrain::Grass,
+        corners[0],
+        corners[1],
+        corners[2],
+    );
+    let quad = LevelTriangle::quad(Surface::Default, Terrain::Grass, corners);
 
-    assert_eq!(c_tri.type_, my_c_tri.type_);
-    assert_eq!(c_tri.force, my_c_tri.force);
-    assert_eq!(c_tri.terrain, my_c_tri.terrain);
-    assert_eq!(c_tri.vertices, my_c_tri.vertices);
+    assert!(normal(&single)[1] > 0.0);
+    for tri in &quad {
+        assert!(normal(tri)[1] > 0.0);
+    }
+
+    let tri = LevelTriangleBuilder::new(corners[0], corners[1], corners[2])
+        .kind(Surface::Ice)
+        .force(3)
+        .build();
+    assert_eq!(tri.kind, Surface::Ice);
+    assert_eq!(tri.force, 3);
+}
+
+#[test]
+fn raycast() {
+    let (_lock, sm64, _mario) = test_mario();
+
+    let down = Point3 {
+        x: 0.0,
+        y: -1.0,
+        z: 0.0,
+    };
+    let hit = sm64
+        .raycast(
+            Point3 {
+                x: 100.0,
+                y: 500.0,
+                z: -200.0,
+            },
+            down,
+            1000.0,
+        )
+        .unwrap();
+
+    assert!(hit.point.y.abs() < 0.01);
+    assert!((hit.distance - 500.0).abs() < 0.01);
+    assert!(hit.normal.y > 0.99);
+    assert_eq!(hit.surface, Some(Surface::Default));
+
+    let short = sm64.raycast(
+        Point3 {
+            x: 100.0,
+            y: 500.0,
+            z: -200.0,
+        },
+        down,
+        100.0,
+    );
+    assert!(short.is_none());
+}
+
+#[test]
+fn floor_height() {
+    let (_lock, sm64, _mario) = test_mario();
+
+    let (height, surface, terrain) = sm64.floor_height(250.0, 300.0, -400.0).unwrap();
+    assert_eq!(height, 0.0);
+    assert_eq!(surface, Some(Surface::Default));
+    assert!(matches!(terrain, Some(Terrain::Grass)));
+
+    assert!(sm64.floor_height(250.0, -300.0, -400.0).is_none());
+    assert!(sm64.floor_height(5000.0, 300.0, 0.0).is_none());
+}
+
+#[test]
+fn dynamic_surface_transform() {
+    let (_lock, mut sm64, _mario) = test_mario();
+
+    let geometry = LevelTriangle::quad(
+        Surface::Default,
+        Terrain::Stone,
+        [
+            Point3 {
+                x: -50,
+                y: 0,
+                z: -50,
+            },
+            Point3 {
+                x: -50,
+                y: 0,
+                z: 50,
+            },
+            Point3 { x: 50, y: 0, z: 50 },
+            Point3 {
+                x: 50,
+                y: 0,
+                z: -50,
+            },
+        ],
+    );
+    let start = SurfaceTransform {
+        position: Point3 {
+            x: 0.0,
+            y: 200.0,
+            z: 0.0,
+        },
+        euler_rotation: Point3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    };
+    let mut surface = sm64.create_dynamic_surface(&geometry, start);
+    assert_eq!(surface.current_transform().position.y, 200.0);
+
+    surface.transform(SurfaceTransform {
+        position: Point3 {
+            x: 10.0,
+            y: 300.0,
+            z: -20.0,
+        },
+        euler_rotation: Point3 {
+            x: 0.0,
+            y: 45.0,
+            z: 0.0,
+        },
+    });
+
+    let current = surface.current_transform();
+    assert_eq!(current.position.x, 10.0);
+    assert_eq!(current.position.y, 300.0);
+    assert_eq!(current.position.z, -20.0);
+    assert_eq!(current.euler_rotation.y, 45.0);
+}
+
+#[test]
+fn dynamic_surface_set_geometry() {
+    let (_lock, mut sm64, mut mario) = test_mario();
+
+    let platform = |size: i16| {
+        LevelTriangle::quad(
+            Surface::Default,
+            Terrain::Stone,
+            [
+                Point3 {
+                    x: -size,
+                    y: 0,
+                    z: -size,
+                },
+                Point3 {
+                    x: -size,
+                    y: 0,
+                    z: size,
+                },
+                Point3 {
+                    x: size,
+                    y: 0,
+                    z: size,
+                },
+                Point3 {
+                    x: size,
+                    y: 0,
+                    z: -size,
+                },
+            ],
+        )
+    };
+    let transform = SurfaceTransform {
+        position: Point3 {
+            x: 0.0,
+            y: 100.0,
+            z: 0.0,
+        },
+        euler_rotation: Point3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    };
+    let mut surface = sm64.create_dynamic_surface(&platform(100), transform);
+
+    mario.set_position(Point3 {
+        x: 0.0,
+        y: 100.0,
+        z: 0.0,
+    });
+    for _ in 0..30 {
+        mario.tick(MarioInput::default());
+    }
+
+    surface.set_geometry(&platform(300));
+    assert_eq!(surface.current_transform().position.y, 100.0);
+
+    for _ in 0..30 {
+        let state = mario.tick(MarioInput::default());
+        assert!((state.position.y - 100.0).abs() < 1.0);
+    }
+}
+
+#[test]
+fn dynamic_surface_carries_mario() {
+    let (_lock, mut sm64, mut mario) = test_mario();
+
+    let geometry = LevelTriangle::quad(
+        Surface::Default,
+        Terrain::Stone,
+        [
+            Point3 {
+                x: -200,
+                y: 0,
+                z: -200,
+            },
+            Point3 {
+                x: -200,
+                y: 0,
+                z: 200,
+            },
+            Point3 {
+                x: 200,
+                y: 0,
+                z: 200,
+            },
+            Point3 {
+                x: 200,
+                y: 0,
+                z: -200,
+            },
+        ],
+    );
+    let mut transform = SurfaceTransform {
+        position: Point3 {
+            x: 0.0,
+            y: 100.0,
+            z: 0.0,
+        },
+        euler_rotation: Point3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    };
+    let mut surface = sm64.create_dynamic_surface(&geometry, transform);
+
+    mario.set_position(Point3 {
+        x: 0.0,
+        y: 100.0,
+        z: 0.0,
+    });
+    for _ in 0..30 {
+        surface.transform(transform);
+        mario.tick(MarioInput::default());
+    }
+    let start = mario.tick(MarioInput::default()).position;
+
+    for _ in 0..30 {
+        transform.position.x += 2.0;
+        surface.transform(transform);
+        mario.tick(MarioInput::default());
+    }
+    let end = mario.tick(MarioInput::default()).position;
+
+    assert!((end.x - start.x - 60.0).abs() < 1.0);
+    assert!((end.y - 100.0).abs() < 1.0);
+}
+
+#[test]
+fn audio_tick() {
+    let (_lock, mut sm64, _mario) = test_mario();
+
+    let mut buffer = [0; AUDIO_BUFFER_LEN];
+    sm64.audio_init();
+    sm64.play_sound(Sound::MarioYahoo);
+
+    let written = sm64.audio_tick(0, &mut buffer);
+    assert_eq!(written, AUDIO_BUFFER_LEN);
+
+    // Once the queue is full libsm64 produces two smaller blocks of 528 frames
+    let written = sm64.audio_tick(AUDIO_TARGET_QUEUED_FRAMES, &mut buffer);
+    assert_eq!(written, 528 * 4);
+
+    let mut short = [0; 100];
+    assert_eq!(sm64.audio_tick(0, &mut short), 100);
+}
+
+#[test]
+fn music() {
+    let (_lock, mut sm64, _mario) = test_mario();
+
+    let mut buffer = [0; AUDIO_BUFFER_LEN];
+    sm64.audio_init();
+
+    sm64.play_music(SeqId::LevelGrass.into());
+    for _ in 0..5 {
+        sm64.audio_tick(0, &mut buffer);
+    }
+    assert_eq!(sm64.current_music(), Some(SeqId::LevelGrass.into()));
+
+    sm64.stop_music();
+    for _ in 0..5 {
+        sm64.audio_tick(0, &mut buffer);
+    }
+    assert_eq!(sm64.current_music(), None);
+
+    // With no music playing these are no-ops
+    sm64.stop_music();
+    sm64.fadeout_music(10);
+    assert_eq!(sm64.current_music(), None);
+
+    sm64.play_music(SeqId::LevelBossKoopa.into());
+    for _ in 0..5 {
+        sm64.audio_tick(0, &mut buffer);
+    }
+    assert_eq!(sm64.current_music(), Some(SeqId::LevelBossKoopa.into()));
+}
+
+#[test]
+fn volume_and_reverb() {
+    let (_lock, mut sm64, _mario) = test_mario();
+
+    let mut buffer = [0; AUDIO_BUFFER_LEN];
+
+    // Before `audio_init` these are no-ops
+    sm64.set_volume(1.0);
+    sm64.set_reverb(MAX_REVERB);
+    assert_eq!(sm64.audio_tick(0, &mut buffer), 0);
+
+    sm64.audio_init();
+    sm64.play_music(SeqId::LevelGrass.into());
+
+    let mut is_audible = |sm64: &mut Sm64| {
+        let mut audible = false;
+        for _ in 0..30 {
+            assert_eq!(sm64.audio_tick(0, &mut buffer), AUDIO_BUFFER_LEN);
+            audible = buffer.iter().any(|&sample| sample != 0);
+        }
+        audible
+    };
+
+    for volume in [0.5, 1.0, 10.0, f32::INFINITY] {
+        sm64.set_volume(volume);
+        assert!(is_audible(&mut sm64), "volume {} is silent", volume);
+    }
+
+    for volume in [-1.0, 0.0, f32::NAN] {
+        sm64.set_volume(volume);
+        assert!(!is_audible(&mut sm64), "volume {} is audible", volume);
+    }
+
+    sm64.set_volume(1.0);
+    for level in [0, 64, MAX_REVERB, u8::MAX] {
+        sm64.set_reverb(level);
+        assert!(is_audible(&mut sm64), "reverb {} is silent", level);
+    }
+}
+
+#[test]
+fn set_animation() {
+    let (_lock, mut sm64, mut mario) = test_mario();
+    let mut control = sm64.create_mario(0, 0, 0).unwrap();
+
+    for _ in 0..30 {
+        mario.tick(MarioInput::default());
+        control.tick(MarioInput::default());
+    }
+
+    // Both start the star dance, but only `mario` skips ahead into the animation
+    mario.set_action(Action::StarDanceNoExit);
+    mario.set_animation(MarioAnimation::StarDance);
+    mario.set_anim_frame(30);
+    control.set_action(Action::StarDanceNoExit);
+
+    let state = mario.tick(MarioInput::default());
+    control.tick(MarioInput::default());
+
+    assert_eq!(
+        Action::from_raw(state.action),
+        Some(Action::StarDanceNoExit)
+    );
+    assert!(mario.geometry().triangles().next().is_some());
+    assert_ne!(mario.geometry().positions(), control.geometry().positions());
+}
+
+#[test]
+fn set_water_level() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    for _ in 0..30 {
+        mario.tick(MarioInput::default());
+    }
+    mario.set_water_level(500);
+
+    let mut submerged = false;
+    for _ in 0..10 {
+        let state = mario.tick(MarioInput::default());
+        submerged |= state.action_group() == ActionGroup::Submerged;
+    }
+
+    assert!(submerged);
+}
+
+#[test]
+fn reset() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    let state = mario.tick(MarioInput::default());
+    mario.take_damage(
+        3,
+        Point3 {
+            x: state.position.x + 100.0,
+            ..state.position
+        },
+    );
+    mario.tick(MarioInput::default());
+
+    let spawn = Point3 {
+        x: 200.0,
+        y: 0.0,
+        z: -300.0,
+    };
+    mario.reset(spawn).unwrap();
+    let state = mario.tick(MarioInput::default());
+
+    assert_eq!(state.health, 0x880);
+    assert!((state.position.x - spawn.x).abs() < 1.0);
+    assert!((state.position.z - spawn.z).abs() < 1.0);
+
+    // The damage that was still draining when Mario was reset is cancelled
+    for _ in 0..30 {
+        assert_eq!(mario.tick(MarioInput::default()).health, 0x880);
+    }
+
+    let below = Point3 {
+        x: 0.0,
+        y: -500.0,
+        z: 0.0,
+    };
+    assert!(matches!(
+        mario.reset(below),
+        Err(Error::InvalidMarioPosition)
+    ));
+}
+
+#[test]
+fn surface_terrain_from_u16() {
+    assert_eq!(Surface::try_from(0x0000).unwrap(), Surface::Default);
+    assert_eq!(Surface::try_from(0x0001).unwrap(), Surface::Burning);
+    assert_eq!(Surface::try_from(0x00D3).unwrap(), Surface::PaintingWarpD3);
+    assert!(matches!(
+        Surface::try_from(0x0002),
+        Err(Error::UnknownSurface(0x0002))
+    ));
+    assert_eq!(Surface::Burning.as_u16(), 0x0001);
+
+    assert!(matches!(Terrain::try_from(0x0005), Ok(Terrain::Water)));
+    assert!(matches!(
+        Terrain::try_from(0x0008),
+        Err(Error::UnknownTerrain(0x0008))
+    ));
+    assert_eq!(Terrain::Slide.as_u16(), 0x0006);
+}
+
+#[test]
+fn raw_level_geometry() {
+    let (_lock, mut sm64, _mario) = test_mario();
+
+    let tri = LevelTriangle::new(
+        Surface::Burning,
+        Terrain::Snow,
+        Point3 {
+            x: -1000,
+            y: 100,
+            z: -1000,
+        },
+        Point3 {
+            x: -1000,
+            y: 100,
+            z: 1000,
+        },
+        Point3 {
+            x: 1000,
+            y: 100,
+            z: 1000,
+        },
+    );
+    let raw = RawLevelTriangle::from(tri);
+    assert_eq!(raw.kind, 0x0001);
+    assert_eq!(raw.terrain, 0x0002);
+    assert_eq!(LevelTriangle::try_from(raw).unwrap().kind, Surface::Burning);
+
+    let modded = RawLevelTriangle {
+        kind: 0x0002,
+        ..raw
+    };
+    assert!(matches!(
+        LevelTriangle::try_from(modded),
+        Err(Error::UnknownSurface(0x0002))
+    ));
+
+    sm64.load_level_geometry_raw(&[modded]);
+    let hit = sm64
+        .raycast(
+            Point3 {
+                x: -500.0,
+                y: 500.0,
+                z: 500.0,
+            },
+            Point3 {
+                x: 0.0,
+                y: -1.0,
+                z: 0.0,
+            },
+            1000.0,
+        )
+        .unwrap();
+
+    assert!((hit.point.y - 100.0).abs() < 0.01);
+    assert_eq!(hit.surface, None);
+
+    let (height, surface, _) = sm64.floor_height(-500.0, 500.0, 500.0).unwrap();
+    assert!((height - 100.0).abs() < 0.01);
+    assert_eq!(surface, None);
+}
+
+#[test]
+fn sm64_builder() {
+    assert!(matches!(Sm64Builder::new().build(), Err(Error::MissingRom)));
+
+    let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut builder = Sm64Builder::new();
+    builder
+        .rom(test_rom())
+        .region(RomRegion::Us)
+        .debug_handler(|_| ())
+        .enable_audio(true);
+
+    let mut sm64 = builder.build().unwrap();
+    assert_eq!(sm64.region(), RomRegion::Us);
+    assert!(DEBUG_HANDLER.lock().unwrap().is_some());
+
+    let mut buffer = [0; AUDIO_BUFFER_LEN];
+    assert!(sm64.audio_tick(0, &mut buffer) > 0);
+
+    // A failed build leaves the live instance's handler alone
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let handler_messages = messages.clone();
+    let second = Sm64Builder::new()
+        .rom(test_rom())
+        .debug_handler(move |message| handler_messages.lock().unwrap().push(message.to_string()))
+        .build();
+    assert!(matches!(second, Err(Error::AlreadyInitialized)));
+    unsafe { debug_print(c"mario".as_ptr()) };
+    assert!(messages.lock().unwrap().is_empty());
+    assert!(DEBUG_HANDLER.lock().unwrap().is_some());
+
+    drop(sm64);
+    assert!(DEBUG_HANDLER.lock().unwrap().is_none());
+
+    assert!(matches!(builder.build(), Err(Error::MissingRom)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn level_save_load() {
+    let floor = LevelTriangle::quad(
+        Surface::Default,
+        Terrain::Grass,
+        [
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: -1000,
+            },
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: -1000,
+            },
+        ],
+    );
+    let level = Level {
+        geometry: floor.to_vec(),
+        dynamic_surfaces: vec![DynamicSurfaceDef {
+            geometry: floor[..1].to_vec(),
+            transform: SurfaceTransform {
+                position: Point3 {
+                    x: 0.0,
+                    y: 500.0,
+                    z: 0.0,
+                },
+                euler_rotation: Point3::default(),
+            },
+        }],
+        spawn: Point3 { x: 0, y: 100, z: 0 },
+    };
+
+    let mut json = Vec::new();
+    level.save(&mut json).unwrap();
+    let loaded = Level::load(json.as_slice()).unwrap();
+
+    assert_eq!(loaded.geometry.len(), 2);
+    assert_eq!(loaded.geometry[1].vertices, level.geometry[1].vertices);
+    assert_eq!(loaded.dynamic_surfaces.len(), 1);
+    assert_eq!(loaded.dynamic_surfaces[0].transform.position.y, 500.0);
+    assert_eq!(loaded.spawn, level.spawn);
+
+    assert!(matches!(
+        Level::load(&b"{\"geometry\": 1}"[..]),
+        Err(Error::Io(_))
+    ));
+}
+
+#[test]
+fn level_apply() {
+    let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut sm64 = Sm64::new(test_rom()).unwrap();
+
+    let floor = LevelTriangle::quad(
+        Surface::Default,
+        Terrain::Grass,
+        [
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: -1000,
+            },
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: -1000,
+            },
+        ],
+    );
+    let mut level = Level {
+        geometry: floor.to_vec(),
+        dynamic_surfaces: vec![DynamicSurfaceDef {
+            geometry: floor.to_vec(),
+            transform: SurfaceTransform {
+                position: Point3 {
+                    x: 0.0,
+                    y: 500.0,
+                    z: 0.0,
+                },
+                euler_rotation: Point3::default(),
+            },
+        }],
+        spawn: Point3 { x: 0, y: 600, z: 0 },
+    };
+
+    let mut loaded = level.apply(&mut sm64).unwrap();
+    assert_eq!(loaded.dynamic_surfaces.len(), 1);
+    for _ in 0..30 {
+        loaded.mario.tick(MarioInput::default());
+    }
+    // Mario lands on the dynamic surface rather than falling to the static floor
+    let state = loaded.mario.tick(MarioInput::default());
+    assert!((state.position.y - 500.0).abs() < 1.0);
+    drop(loaded);
+
+    level.spawn.y = -100;
+    assert!(matches!(
+        level.apply(&mut sm64),
+        Err(Error::InvalidMarioPosition)
+    ));
+}
+
+#[test]
+fn simulation() {
+    let (_lock, sm64, mario) = test_mario();
+
+    let mut simulation = Simulation::new(sm64, mario);
+    assert_eq!(simulation.frame(), 0);
+
+    for _ in 0..30 {
+        simulation.step(MarioInput::default());
+    }
+
+    assert_eq!(simulation.frame(), 30);
+    assert_eq!(simulation.elapsed(), TICK_DURATION * 30);
+    assert!(simulation.state().health > 0);
+    assert!(simulation.observe().triangles().next().is_some());
+}
+
+#[test]
+fn world_bounds() {
+    let (_lock, mut sm64, mut mario) = test_mario();
+
+    sm64.set_world_bounds(
+        Point3 {
+            x: 100.0,
+            y: 1000.0,
+            z: -100.0,
+        },
+        Point3 {
+            x: -100.0,
+            y: 0.0,
+            z: 100.0,
+        },
+    );
+    assert_eq!(
+        sm64.world_bounds(),
+        Some((
+            Point3 {
+                x: -100.0,
+                y: 0.0,
+                z: -100.0,
+            },
+            Point3 {
+                x: 100.0,
+                y: 1000.0,
+                z: 100.0,
+            },
+        ))
+    );
+
+    mario.set_position(Point3 {
+        x: 500.0,
+        y: 500.0,
+        z: 0.0,
+    });
+    mario.set_velocity(Point3 {
+        x: 50.0,
+        y: 0.0,
+        z: 0.0,
+    });
+    let state = mario.tick(MarioInput::default());
+    assert_eq!(state.position.x, 100.0);
+    assert_eq!(state.velocity.x, 0.0);
+
+    // The geometry is moved to the clamped position too
+    let positions = mario.geometry().positions();
+    let center = positions.iter().map(|p| p.x).sum::<f32>() / positions.len() as f32;
+    assert!((center - 100.0).abs() < 50.0);
+
+    sm64.reset();
+    assert_eq!(sm64.world_bounds(), None);
+
+    sm64.set_world_bounds(Point3::default(), Point3::default());
+    sm64.set_handedness(Handedness::Left);
+    drop(mario);
+    drop(sm64);
+    let sm64 = Sm64::new(test_rom()).unwrap();
+    assert_eq!(sm64.world_bounds(), None);
+    assert_eq!(sm64.handedness(), Handedness::Right);
+}
+
+#[test]
+fn reset_keeps_audio() {
+    let (_lock, mut sm64, _mario) = test_mario();
+
+    let mut buffer = [0; AUDIO_BUFFER_LEN];
+    sm64.audio_init();
+    sm64.reset();
+
+    assert_eq!(sm64.audio_tick(0, &mut buffer), AUDIO_BUFFER_LEN);
+    sm64.play_music(SeqId::LevelGrass.into());
+    assert_eq!(sm64.current_music(), Some(SeqId::LevelGrass.into()));
+}
+
+#[test]
+fn terminate_clears_audio() {
+    let (_lock, mut sm64, mario) = test_mario();
+
+    let mut buffer = [0; AUDIO_BUFFER_LEN];
+    sm64.audio_init();
+    drop(mario);
+    sm64.terminate();
+
+    let mut sm64 = Sm64::new(test_rom()).unwrap();
+    assert_eq!(sm64.audio_tick(0, &mut buffer), 0);
+    assert_eq!(sm64.current_music(), None);
+
+    sm64.audio_init();
+    assert_eq!(sm64.audio_tick(0, &mut buffer), AUDIO_BUFFER_LEN);
+}
+
+#[test]
+fn ground_shadow() {
+    let (_lock, _sm64, mut mario) = test_mario();
+
+    assert!(MarioState::default().ground_shadow.is_none());
+
+    let state = mario.tick(MarioInput::default());
+    let (position, scale) = state.ground_shadow.unwrap();
+    assert_eq!(position.y, 0.0);
+    assert_eq!(position.x, state.position.x);
+    assert_eq!(scale, 1.0);
+
+    mario.set_position(Point3 {
+        x: 0.0,
+        y: 1200.0,
+        z: 0.0,
+    });
+    let state = mario.tick(MarioInput::default());
+    let (_, scale) = state.ground_shadow.unwrap();
+    assert_eq!(scale, 0.5);
+
+    mario.set_position(Point3 {
+        x: 0.0,
+        y: -100.0,
+        z: 0.0,
+    });
+    assert!(mario.tick(MarioInput::default()).ground_shadow.is_none());
+}
+
+#[test]
+fn wall_normal() {
+    let (_lock, mut sm64, mut mario) = test_mario();
+
+    let mut geometry = LevelTriangle::quad(
+        Surface::Default,
+        Terrain::Grass,
+        [
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: -1000,
+            },
+            Point3 {
+                x: -1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: 1000,
+            },
+            Point3 {
+                x: 1000,
+                y: 0,
+                z: -1000,
+            },
+        ],
+    )
+    .to_vec();
+    geometry.extend(LevelTriangle::quad(
+        Surface::Default,
+        Terrain::Stone,
+        [
+            Point3 {
+                x: 200,
+                y: 0,
+                z: 500,
+            },
+            Point3 {
+                x: 200,
+                y: 500,
+                z: 500,
+            },
+            Point3 {
+                x: 200,
+                y: 500,
+                z: -500,
+            },
+            Point3 {
+                x: 200,
+                y: 0,
+                z: -500,
+            },
+        ],
+    ));
+    sm64.load_level_geometry(&geometry);
+
+    mario.set_wall_detection(true);
+    assert!(mario.tick(MarioInput::default()).wall_normal.is_none());
+
+    mario.set_position(Point3 {
+        x: 160.0,
+        y: 0.0,
+        z: 0.0,
+    });
+    let normal = mario.tick(MarioInput::default()).wall_normal.unwrap();
+    assert!(normal.x < -0.99);
+
+    mario.set_wall_detection(false);
+    assert!(mario.tick(MarioInput::default()).wall_normal.is_none());
+}
+
+#[test]
+fn geometry_is_dirty() {
+    let mut geo = MarioGeometry::new();
+    geo.update(1);
+    assert!(geo.is_dirty());
+
+    geo.update(1);
+    assert!(!geo.is_dirty());
+
+    geo.position[0].x = 1.0;
+    geo.update(1);
+    assert!(geo.is_dirty());
+
+    // Without a check on the previous tick there is nothing to compare against
+    geo.update(1);
+    geo.update(1);
+    assert!(geo.is_dirty());
+    geo.update(1);
+    assert!(!geo.is_dirty());
+
+    geo.update(2);
+    assert!(geo.is_dirty());
+}
+
+#[test]
+fn ledge_grab() {
+    let state = |action: Action| MarioState {
+        action: action.into(),
+        ..Default::default()
+    };
+
+    assert_eq!(state(Action::Idle).ledge_grab(), None);
+    assert_eq!(state(Action::LedgeGrab).ledge_grab(), Some(0.0));
+
+    let slow_1 = state(Action::LedgeClimbSlow1).ledge_grab().unwrap();
+    let slow_2 = state(Action::LedgeClimbSlow2).ledge_grab().unwrap();
+    assert!(slow_1 > 0.0 && slow_1 < slow_2 && slow_2 < 1.0);
+}
+
+#[test]
+fn caps() {
+    let state = MarioState {
+        flags: MarioFlags::NORMAL_CAP | MarioFlags::WING_CAP | MarioFlags::CAP_ON_HEAD,
+        ..Default::default()
+    };
+
+    assert_eq!(state.caps(), CapFlags::NORMAL | CapFlags::WING);
+    assert!(state.caps().contains(Cap::Wing.into()));
+    assert!(!state.caps().contains(CapFlags::METAL));
+    assert!(MarioState::default().caps().is_empty());
+}
+
+#[test]
+fn activity_level() {
+    let state = |action: Action| MarioState {
+        action: action.into(),
+        ..Default::default()
+    };
+
+    assert!(state(Action::Idle).is_idle());
+    assert!(!state(Action::Walking).is_idle());
+    assert_eq!(
+        state(Action::Walking).activity_level(),
+        ActivityLevel::Walking
+    );
+    assert_eq!(
+        state(Action::Jump).activity_level(),
+        ActivityLevel::Airborne
+    );
+    assert_eq!(
+        state(Action::LedgeGrab).activity_level(),
+        ActivityLevel::Interacting
+    );
 }